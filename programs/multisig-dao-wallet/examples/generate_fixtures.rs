@@ -0,0 +1,137 @@
+//! Writes the wallet config accounts in `tests/fixtures` that Anchor.toml
+//! loads into the test validator. Rerun it whenever `WalletConfig` changes:
+//!
+//! ```sh
+//! cargo run -p multisig-dao-wallet --example generate_fixtures
+//! ```
+
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use base64::Engine;
+use multisig_dao_wallet::{
+    Member, WalletConfig, MAX_AWAITED_DEPENDENCIES, WALLET_CONFIG_SPACES, WALLET_CONFIG_VERSION,
+    WALLET_CONFIG_VERSION_OFFSET,
+};
+
+/// Serialized size of the fields each version appended to the layout,
+/// starting at version 2
+const APPENDED_BYTES: [usize; WALLET_CONFIG_VERSION as usize - 1] = [8 * MAX_AWAITED_DEPENDENCIES];
+
+/// Version no program supports
+const FUTURE_VERSION: u8 = 255;
+
+/// Public key of `Keypair.fromSeed("legacy-wallet-authority-seed-v01")`,
+/// which the tests sign with
+const LEGACY_WALLET_AUTHORITY: &str = "DrKjLLJidXCLMTM36H8k56ntRv8K5t274v7b2hWYHyJm";
+
+/// Stands in for bytes left behind in an account's padding when one of its
+/// lists shrank
+const STALE: u8 = 0x55;
+
+/// Public key of `Keypair.fromSeed("unversioned-wallet-authority-001")`,
+/// which the tests sign with
+const UNVERSIONED_WALLET_AUTHORITY: &str = "BcrBKzkdPESy9mPzW1APvT6qLEjt2GEvVhwLZ6YdosQz";
+
+fn main() {
+    let future_authority = Pubkey::new_from_array(*b"future-version-wallet-creator-01");
+    let data = account_data(&wallet_config(future_authority, FUTURE_VERSION), 0);
+    write_fixture("future-version-wallet.json", future_authority, data);
+
+    let legacy_authority = Pubkey::from_str(LEGACY_WALLET_AUTHORITY).unwrap();
+    let data = account_data(&wallet_config(legacy_authority, 1), STALE);
+    write_fixture("legacy-v1-wallet.json", legacy_authority, data);
+
+    // Before versioning, the signer list's length sat where the version is
+    // now; with one signer, that byte reads as version 1
+    let unversioned_authority = Pubkey::from_str(UNVERSIONED_WALLET_AUTHORITY).unwrap();
+    let mut data = account_data(&wallet_config(unversioned_authority, 1), 0);
+    data.remove(WALLET_CONFIG_VERSION_OFFSET);
+    write_fixture("unversioned-wallet.json", unversioned_authority, data);
+}
+
+/// A wallet config stamped with `version` whose authority, creator and only
+/// signer is `authority`
+fn wallet_config(authority: Pubkey, version: u8) -> WalletConfig {
+    // Every field of an all-zero buffer decodes to its empty or zero value
+    let zeros = vec![0; WalletConfig::INIT_SPACE];
+    let mut wallet_config = WalletConfig::deserialize(&mut &zeros[..]).unwrap();
+    let mut member = Member::deserialize(&mut &zeros[..]).unwrap();
+    member.address = authority;
+    member.is_active = true;
+    member.weight = 1;
+
+    let (_, bump) = Pubkey::find_program_address(
+        &[b"wallet_config", authority.as_ref()],
+        &multisig_dao_wallet::ID,
+    );
+    wallet_config.authority = authority;
+    wallet_config.version = version;
+    wallet_config.creator = authority;
+    wallet_config.bump = bump;
+    wallet_config.signers = vec![authority];
+    wallet_config.members = vec![member];
+    wallet_config.authority_is_signer = true;
+    wallet_config.threshold = 1;
+    wallet_config.admin_threshold = 1;
+    wallet_config.emergency_threshold = 1;
+    wallet_config.proposal_timeout = 86_400;
+    wallet_config.spending_limit = 1_000_000_000;
+    wallet_config.spending_period = 86_400;
+    wallet_config.last_spending_reset = 1_700_000_000;
+    wallet_config.is_active = true;
+    wallet_config
+}
+
+/// The discriminator and serialized `wallet_config` in the layout of its
+/// version, without the fields appended since, padded to that version's
+/// account size with `padding`. Versions newer than this program's get the
+/// current layout.
+fn account_data(wallet_config: &WalletConfig, padding: u8) -> Vec<u8> {
+    let layout = usize::from(wallet_config.version.min(WALLET_CONFIG_VERSION));
+    let appended: usize = APPENDED_BYTES[layout - 1..].iter().sum();
+
+    let mut data = WalletConfig::DISCRIMINATOR.to_vec();
+    data.extend(wallet_config.try_to_vec().unwrap());
+    data.truncate(data.len() - appended);
+    data.resize(WALLET_CONFIG_SPACES[layout - 1], padding);
+    data
+}
+
+/// Write `data` as a rent-exempt program account at the wallet config PDA of
+/// `creator`
+fn write_fixture(name: &str, creator: Pubkey, data: Vec<u8>) {
+    let (address, _) = Pubkey::find_program_address(
+        &[b"wallet_config", creator.as_ref()],
+        &multisig_dao_wallet::ID,
+    );
+    let fixture = format!(
+        r#"{{
+  "pubkey": "{address}",
+  "account": {{
+    "lamports": {lamports},
+    "data": [
+      "{data}",
+      "base64"
+    ],
+    "owner": "{owner}",
+    "executable": false,
+    "rentEpoch": 0,
+    "space": {space}
+  }}
+}}
+"#,
+        lamports = Rent::default().minimum_balance(data.len()),
+        data = base64::engine::general_purpose::STANDARD.encode(&data),
+        owner = multisig_dao_wallet::ID,
+        space = data.len(),
+    );
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../tests/fixtures")
+        .join(name);
+    fs::write(&path, fixture).unwrap();
+    println!("Wrote {} at {}", path.display(), address);
+}
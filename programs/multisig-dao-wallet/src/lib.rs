@@ -1,17 +1,109 @@
 #![allow(deprecated)]
 use anchor_lang::prelude::*;
-// use anchor_spl::{
-//     associated_token::AssociatedToken,
-//     token::{Mint, Token, TokenAccount, Transfer},
-// };
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, TransferChecked};
 
 declare_id!("Dbte4Uv7CcmKpvnbV9jo3vQzL8cPggGm71TQHzTgDQsR");
 
+/// Maximum number of signers (and members) a wallet can hold
+pub const MAX_SIGNERS: usize = 10;
+
+/// Number of emergency overrides kept in a wallet's log
+pub const EMERGENCY_LOG_CAPACITY: usize = 5;
+
+/// Number of spending limit and signer changes kept in a wallet's log
+pub const CONFIG_LOG_CAPACITY: usize = 8;
+
+/// Maximum number of programs in a wallet's allowlist
+pub const MAX_ALLOWED_PROGRAMS: usize = 10;
+
+/// Maximum number of instructions per proposal. Every slot is paid for in
+/// each proposal's rent, so raising this makes all proposals more expensive.
+pub const MAX_INSTRUCTIONS: usize = 8;
+
+/// Maximum number of distinct accounts per proposal instruction
+pub const MAX_INSTRUCTION_ACCOUNTS: usize = 3;
+
+/// Maximum number of options on a multi-option proposal
+pub const MAX_PROPOSAL_OPTIONS: usize = 4;
+
+/// Maximum length in bytes of a proposal instruction's data
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 64;
+
+/// Maximum length in bytes of a proposal description
+pub const MAX_DESCRIPTION_LEN: usize = 50;
+
+/// Basis points in a whole, for `threshold_bps`
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Maximum number of per-tag spending budgets
+pub const MAX_BUDGETS: usize = 4;
+
+/// Maximum lamports a wallet's policy may pay executors per proposal
+pub const MAX_EXECUTION_BOUNTY: u64 = 10_000_000;
+
+/// Maximum number of dependencies a wallet's open proposals may be waiting on
+pub const MAX_AWAITED_DEPENDENCIES: usize = 8;
+
+/// Layout version of `WalletSnapshot`, bumped whenever its fields (including
+/// those of `WalletPolicy`) change
+pub const SNAPSHOT_VERSION: u8 = 11;
+
+/// Byte offset of `Proposal::status_code` in a proposal account, after the
+/// discriminator, wallet, proposer and tag, for memcmp filtering by status
+pub const PROPOSAL_STATUS_CODE_OFFSET: usize = 8 + 32 + 32 + 16;
+
+/// Layout version of `WalletConfig` accounts this program reads and writes.
+/// Older accounts are brought up to it by `migrate_wallet_config`
+pub const WALLET_CONFIG_VERSION: u8 = 2;
+
+/// Layout version of `Proposal` accounts this program reads and writes
+pub const PROPOSAL_VERSION: u8 = 1;
+
+/// Byte offset of `WalletConfig::version`, after the discriminator and
+/// authority, so it can be read without deserializing the account
+pub const WALLET_CONFIG_VERSION_OFFSET: usize = 8 + 32;
+
+/// Account size of each `WalletConfig` layout version, starting at version 1.
+/// Later versions only append fields, of types that decode from any bytes, so
+/// `migrate_wallet_config` can read an older account as the current layout
+pub const WALLET_CONFIG_SPACES: [usize; WALLET_CONFIG_VERSION as usize] = [3956, 8 + WalletConfig::INIT_SPACE];
+
+/// Byte offset of `Proposal::version`, right after `status_code`
+pub const PROPOSAL_VERSION_OFFSET: usize = PROPOSAL_STATUS_CODE_OFFSET + 1;
+
+/// Reason hash of a vote cast without an off-chain explanation
+pub const NO_REASON: [u8; 32] = [0; 32];
+
+/// `check_invariants` flag: the active voting signers can't meet every
+/// category threshold
+pub const INVARIANT_THRESHOLD: u8 = 1 << 0;
+
+/// `check_invariants` flag: members and signers are out of sync
+pub const INVARIANT_MEMBERS: u8 = 1 << 1;
+
+/// `check_invariants` flag: the spending period is not positive or the
+/// spending window starts in the future
+pub const INVARIANT_SPENDING: u8 = 1 << 2;
+
+/// `check_invariants` flag: open and finished proposals outnumber the
+/// proposals ever created
+pub const INVARIANT_PROPOSAL_COUNTS: u8 = 1 << 3;
+
 #[program]
 pub mod multisig_dao_wallet {
     use super::*;
 
     /// Initialize the multisig wallet with initial signers and threshold
+    ///
+    /// `admin_threshold` and `emergency_threshold` set the approvals needed
+    /// for Admin and Emergency proposals. Passing 0 picks the default of one
+    /// more (capped at the signer count) or one fewer (at least one) than
+    /// `threshold` respectively. A non-empty `allowed_programs` restricts the
+    /// programs proposals may invoke. `quorum` is the minimum number of
+    /// approvals, rejections and abstentions before a proposal can be approved
+    /// (0 for none).
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_wallet(
         ctx: Context<InitializeWallet>,
         signers: Vec<Pubkey>,
@@ -19,397 +111,4080 @@ pub mod multisig_dao_wallet {
         proposal_timeout: i64,
         spending_limit: u64,
         spending_period: i64,
+        admin_threshold: u8,
+        emergency_threshold: u8,
+        allowed_programs: Vec<Pubkey>,
+        quorum: u8,
     ) -> Result<()> {
-        require!(signers.len() >= threshold as usize, MultisigError::InvalidThreshold);
-        require!(threshold > 0, MultisigError::InvalidThreshold);
-        require!(proposal_timeout > 0, MultisigError::InvalidTimeout);
-        require!(spending_limit > 0, MultisigError::InvalidSpendingLimit);
-
-        let wallet_config = &mut ctx.accounts.wallet_config;
-        wallet_config.authority = ctx.accounts.authority.key();
-        wallet_config.signers = signers.clone();
-        wallet_config.threshold = threshold;
-        wallet_config.proposal_timeout = proposal_timeout;
-        wallet_config.spending_limit = spending_limit;
-        wallet_config.spending_period = spending_period;
-        wallet_config.spending_used = 0;
-        wallet_config.last_spending_reset = Clock::get()?.unix_timestamp;
-        wallet_config.is_active = true;
-        wallet_config.proposal_count = 0;
-        wallet_config.bump = ctx.bumps.wallet_config;
+        let authority = ctx.accounts.authority.key();
+        let max_threshold = u8::try_from(signers.len()).unwrap_or(u8::MAX);
+        let admin_threshold = match admin_threshold {
+            0 => threshold.saturating_add(1).min(max_threshold),
+            configured => configured,
+        };
+        let emergency_threshold = match emergency_threshold {
+            0 => threshold.saturating_sub(1).max(1),
+            configured => configured,
+        };
 
-        // Initialize members
-        wallet_config.members = Vec::new();
-        for signer in &signers {
-            let member = Member {
-                address: *signer,
-                role: MemberRole::Member,
-                delegate: None,
-                is_active: true,
-            };
-            wallet_config.members.push(member);
-        }
+        // The authority starts as an admin if it signs
+        let members = signers
+            .iter()
+            .map(|signer| MemberSettings {
+                role: if *signer == authority {
+                    MemberRole::Admin
+                } else {
+                    MemberRole::Member
+                },
+                weight: 1,
+            })
+            .collect();
+
+        init_wallet_config(
+            &mut ctx.accounts.wallet_config,
+            authority,
+            ctx.bumps.wallet_config,
+            WalletSnapshot {
+                version: SNAPSHOT_VERSION,
+                signers: signers.clone(),
+                threshold,
+                admin_threshold,
+                emergency_threshold,
+                proposal_timeout,
+                spending_limit,
+                spending_period,
+                allowed_programs,
+                quorum,
+                voting_mode: VotingMode::Count,
+                weight_threshold: 0,
+                threshold_bps: 0,
+                policy: WalletPolicy::default(),
+                guardian: None,
+                members,
+                budgets: Vec::new(),
+            },
+        )?;
 
         msg!("Multisig wallet initialized with {} signers and threshold {}", 
              signers.len(), threshold);
         Ok(())
     }
 
+    /// Initialize a wallet from a snapshot returned by `export_config`
+    ///
+    /// The snapshot is validated like the arguments to `initialize_wallet`.
+    /// The signer of this call becomes the new wallet's authority; proposals,
+    /// spending and pending changes start empty.
+    pub fn initialize_wallet_from_snapshot(
+        ctx: Context<InitializeWallet>,
+        snapshot: WalletSnapshot,
+    ) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let signer_count = snapshot.signers.len();
+        init_wallet_config(&mut ctx.accounts.wallet_config, authority, ctx.bumps.wallet_config, snapshot)?;
+
+        msg!("Multisig wallet initialized from snapshot with {} signers by {}", signer_count, authority);
+        Ok(())
+    }
+
+    /// Export the wallet's settings as a versioned snapshot, excluding
+    /// proposal, spending and pending-change state
+    pub fn export_config(ctx: Context<ExportConfig>) -> Result<WalletSnapshot> {
+        Ok(ctx.accounts.wallet_config.snapshot())
+    }
+
     /// Submit a new transaction proposal
+    ///
+    /// `proposal_id` must equal the wallet's current `proposal_count`; it
+    /// seeds the proposal PDA so a proposer can have many open proposals.
+    /// The count never decreases, so the address of a closed proposal is
+    /// never handed out again. Returns the assigned id and proposal address.
+    /// `metadata_hash` commits to an off-chain proposal document and `tag` is
+    /// a client-defined label; neither affects how the proposal is governed.
+    /// A `price_condition` makes execution wait until an oracle price holds,
+    /// and `depends_on` until an earlier proposal of the wallet has executed.
+    /// Every signer in `required_approvers` must approve before the proposal
+    /// is approved, however many other approvals it gathers.
+    #[allow(clippy::too_many_arguments)]
     pub fn add_proposal(
         ctx: Context<AddProposal>,
+        proposal_id: u64,
         description: String,
+        metadata_hash: [u8; 32],
+        tag: [u8; 16],
         category: ProposalCategory,
         instructions: Vec<InstructionData>,
         expiration: i64,
-    ) -> Result<()> {
+        price_condition: Option<PriceCondition>,
+        depends_on: Option<u64>,
+        not_before: Option<i64>,
+        options: Vec<ProposalOption>,
+        required_approvers: Vec<Pubkey>,
+    ) -> Result<NewProposal> {
         // Get the wallet key before taking mutable reference
         let wallet_key = ctx.accounts.wallet_config.key();
         let wallet_config = &mut ctx.accounts.wallet_config;
         require!(wallet_config.is_active, MultisigError::WalletInactive);
         
+        require!(proposal_id == wallet_config.proposal_count, MultisigError::InvalidProposalId);
+        let max_open = wallet_config.policy.max_open_proposals;
+        require!(
+            max_open == 0 || wallet_config.active_proposal_count < u64::from(max_open),
+            MultisigError::TooManyOpenProposals
+        );
+        require!(
+            depends_on.is_none_or(|dependency_id| dependency_id < proposal_id),
+            MultisigError::InvalidProposalId
+        );
+        validate_description(&description)?;
+        let mut instructions = instructions;
+        validate_instructions(wallet_config, &mut instructions)?;
+        validate_options(&category, &options, instructions.len())?;
+        validate_required_approvers(wallet_config, &required_approvers)?;
+        if let ProposalCategory::SignerChange { signers, threshold } = &category {
+            validate_signer_set(wallet_config, signers, *threshold)?;
+        }
+        if let ProposalCategory::SpendingLimitChange { limit, .. } = &category {
+            require!(*limit > 0, MultisigError::InvalidSpendingLimit);
+        }
+        if let ProposalCategory::TokenTransfer { amount, max_amount, .. } = &category {
+            require!(amount <= max_amount, MultisigError::AmountExceedsMax);
+        }
+        
+        // Admin and emergency proposals are restricted by role
+        let proposer = ctx.accounts.proposer.key();
+        if matches!(category, ProposalCategory::Admin | ProposalCategory::Emergency) {
+            let allowed = member_role(wallet_config, &proposer)
+                .is_some_and(|role| role.can_propose(&category));
+            if !allowed {
+                msg!("Proposer {} lacks the role required for this proposal category", proposer);
+                return err!(MultisigError::NotAuthorized);
+            }
+        }
+        
         let current_time = Clock::get()?.unix_timestamp;
         require!(expiration > current_time, MultisigError::InvalidExpiration);
+        require!(
+            not_before.is_none_or(|not_before| not_before < expiration),
+            MultisigError::InvalidExpiration
+        );
+        require!(
+            expiration <= current_time.saturating_add(wallet_config.proposal_timeout),
+            MultisigError::ExpirationExceedsTimeout
+        );
+
+        require!(wallet_config.is_active_member(&proposer), MultisigError::MemberInactive);
+
+        // Members may not submit proposals faster than the policy allows
+        let min_interval = wallet_config.policy.min_proposal_interval;
+        if let Some(member) = wallet_config.members.iter_mut().find(|member| member.address == proposer) {
+            require!(
+                current_time >= member.last_proposal_at.saturating_add(min_interval),
+                MultisigError::ProposalRateLimited
+            );
+            member.last_proposal_at = current_time;
+        }
 
         let proposal = &mut ctx.accounts.proposal;
         proposal.wallet = wallet_key;
         proposal.proposer = ctx.accounts.proposer.key();
+        proposal.tag = tag;
+        proposal.version = PROPOSAL_VERSION;
         proposal.description = description;
         proposal.category = category;
         proposal.instructions = instructions;
         proposal.expiration = expiration;
-        proposal.status = ProposalStatus::Pending;
+        proposal.set_status(ProposalStatus::Pending);
         proposal.approvals = Vec::new();
         proposal.rejections = Vec::new();
+        proposal.abstentions = Vec::new();
         proposal.created_at = current_time;
-        proposal.id = wallet_config.proposal_count;
+        proposal.approved_at = None;
+        proposal.executed_by = None;
+        proposal.metadata_hash = metadata_hash;
+        proposal.executed_instruction_count = 0;
+        proposal.price_condition = price_condition;
+        proposal.fee_payer = ctx.accounts.fee_payer.key();
+        proposal.depends_on = depends_on;
+        if let Some(dependency_id) = depends_on {
+            wallet_config.await_dependency(dependency_id)?;
+        }
+        proposal.failed_attempts = 0;
+        proposal.last_execution_error = None;
+        proposal.signer_set_version = wallet_config.signer_set_version;
+        proposal.not_before = not_before;
+        proposal.options = options;
+        proposal.selected_option = None;
+        proposal.required_approvers = required_approvers;
+        proposal.id = proposal_id;
         proposal.bump = ctx.bumps.proposal;
+        validate_proposal_size(proposal)?;
+
+        wallet_config.proposal_count = wallet_config
+            .proposal_count
+            .checked_add(1)
+            .ok_or(MultisigError::ArithmeticOverflow)?;
+        wallet_config.active_proposal_count = wallet_config.active_proposal_count.saturating_add(1);
 
-        wallet_config.proposal_count += 1;
+        emit!(ProposalCreated {
+            wallet: wallet_key,
+            proposal_id,
+            proposer: proposal.proposer,
+            category: proposal.category.clone(),
+            timestamp: current_time,
+        });
+
+        // Submitting a proposal endorses it when the policy says so; only
+        // signers' approvals count, multi-option proposals leave the proposer
+        // to pick an option, and a proposer later in an ordered chain of
+        // required approvers waits for their turn
+        if wallet_config.policy.auto_approve_proposer
+            && wallet_config.signers.contains(&proposer)
+            && proposal.options.is_empty()
+            && wallet_config.approval_in_order(proposal, &proposer)
+        {
+            record_approval(wallet_config, proposal, &proposer, NO_REASON, 0, current_time)?;
+        }
 
         msg!("Proposal {} created by {}", proposal.key(), ctx.accounts.proposer.key());
-        Ok(())
+        Ok(NewProposal {
+            id: proposal_id,
+            proposal: proposal.key(),
+        })
     }
 
     /// Approve a proposal
-    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
-        let wallet_config = &ctx.accounts.wallet_config;
+    ///
+    /// A signer may be another wallet's config PDA, which approves by
+    /// executing one of its own proposals that invokes this instruction. The
+    /// PDA signs that invocation, so a child multisig can hold a signer slot
+    /// in a parent wallet.
+    ///
+    /// `reason` is a hash of an off-chain explanation stored with the
+    /// approval, or `NO_REASON` when none is given. `option` is the index of
+    /// the option voted for on a multi-option proposal and 0 otherwise.
+    pub fn approve_proposal(ctx: Context<ApproveProposal>, reason: [u8; 32], option: u8) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
         let proposal = &mut ctx.accounts.proposal;
         
         require!(wallet_config.is_active, MultisigError::WalletInactive);
+        require!(proposal.wallet == wallet_config.key(), MultisigError::WalletMismatch);
         require!(proposal.status == ProposalStatus::Pending, MultisigError::ProposalNotPending);
         
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(proposal.expiration > current_time, MultisigError::ProposalExpired);
+        require!(
+            wallet_config.approval_in_order(proposal, &ctx.accounts.approver.key()),
+            MultisigError::OutOfOrderApproval
+        );
+
+        record_approval(wallet_config, proposal, &ctx.accounts.approver.key(), reason, option, current_time)
+    }
+
+    /// Approve several proposals in one transaction
+    ///
+    /// Proposals are passed as writable `remaining_accounts`. Ones that are no
+    /// longer pending, have expired, need an option chosen, already have the
+    /// approver's vote or are waiting on earlier required approvers are
+    /// skipped; the ids of the proposals approved are returned.
+    pub fn approve_proposals_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ApproveProposalsBatch<'info>>,
+    ) -> Result<Vec<u64>> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let approver = ctx.accounts.approver.key();
+        let current_time = Clock::get()?.unix_timestamp;
+        let mut approved_ids = Vec::new();
+
+        for info in ctx.remaining_accounts {
+            let mut proposal = Account::<Proposal>::try_from(info)?;
+            require!(proposal.wallet == wallet_config.key(), MultisigError::WalletMismatch);
+            require!(is_current_proposal(&proposal), MultisigError::UnsupportedAccountVersion);
+
+            if proposal.status != ProposalStatus::Pending
+                || proposal.expiration <= current_time
+                || !proposal.options.is_empty()
+            {
+                msg!("Skipping proposal {}", proposal.key());
+                continue;
+            }
+            // A delegate whose delegators have all approved has no vote left
+            let already_voted = match wallet_config.resolve_voter(&approver, &proposal.approvers()) {
+                Ok((voter, _)) => proposal.has_voted(&voter),
+                Err(error) => error == MultisigError::DelegatedApprovalConflict.into(),
+            };
+            if already_voted {
+                msg!("Skipping proposal {}: already voted on", proposal.key());
+                continue;
+            }
+            if !wallet_config.approval_in_order(&proposal, &approver) {
+                msg!("Skipping proposal {}: earlier required approvers have not approved", proposal.key());
+                continue;
+            }
+
+            record_approval(wallet_config, &mut proposal, &approver, NO_REASON, 0, current_time)?;
+            proposal.exit(&crate::ID)?;
+            approved_ids.push(proposal.id);
+        }
+
+        msg!("Approved {} of {} proposals", approved_ids.len(), ctx.remaining_accounts.len());
+        Ok(approved_ids)
+    }
+
+    /// Refresh the caller's earlier approval of a pending or approved
+    /// proposal so it counts again under the wallet's approval validity window
+    pub fn reconfirm_approval(ctx: Context<ReconfirmApproval>) -> Result<()> {
+        let wallet_config = &ctx.accounts.wallet_config;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+        require!(
+            matches!(proposal.status, ProposalStatus::Pending | ProposalStatus::Approved),
+            MultisigError::ProposalNotPending
+        );
+
         let current_time = Clock::get()?.unix_timestamp;
         require!(proposal.expiration > current_time, MultisigError::ProposalExpired);
 
         let approver = ctx.accounts.approver.key();
         require!(wallet_config.signers.contains(&approver), MultisigError::NotAuthorized);
+        require!(wallet_config.is_active_member(&approver), MultisigError::MemberInactive);
+        let approval = proposal
+            .approvals
+            .iter_mut()
+            .find(|approval| approval.signer == approver)
+            .ok_or(MultisigError::ApprovalNotFound)?;
+        approval.approved_at = current_time;
+        let reason = approval.reason;
+        let option = approval.option;
+
+        emit!(ProposalApproved {
+            wallet: wallet_config.key(),
+            proposal_id: proposal.id,
+            approver,
+            delegate: None,
+            reason,
+            option,
+            timestamp: current_time,
+        });
 
-        // Check if already approved
-        require!(!proposal.approvals.contains(&approver), MultisigError::AlreadyApproved);
+        msg!("Approval of proposal {} reconfirmed by {}", proposal.key(), approver);
+        Ok(())
+    }
 
-        proposal.approvals.push(approver);
+    /// Reject a proposal
+    ///
+    /// `reason` is a hash of an off-chain explanation stored with the
+    /// rejection, or `NO_REASON` when none is given.
+    pub fn reject_proposal(ctx: Context<RejectProposal>, reason: [u8; 32]) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        let proposal = &mut ctx.accounts.proposal;
         
-        // Check if threshold is met
-        let required_threshold = match proposal.category {
-            ProposalCategory::Regular => wallet_config.threshold,
-            ProposalCategory::Admin => wallet_config.threshold + 1,
-            ProposalCategory::Emergency => wallet_config.threshold - 1,
-        };
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+        require!(proposal.wallet == wallet_config.key(), MultisigError::WalletMismatch);
+        require!(proposal.status == ProposalStatus::Pending, MultisigError::ProposalNotPending);
+        
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(proposal.expiration > current_time, MultisigError::ProposalExpired);
+
+        let rejector = ctx.accounts.rejector.key();
+        require!(wallet_config.signers.contains(&rejector), MultisigError::NotAuthorized);
+        require!(wallet_config.is_active_member(&rejector), MultisigError::MemberInactive);
+
+        // Check if already rejected
+        require!(!proposal.has_rejected(&rejector), MultisigError::AlreadyRejected);
+        require!(!proposal.has_voted(&rejector), MultisigError::AlreadyVoted);
+
+        proposal.rejections.push(Rejection {
+            signer: rejector,
+            reason,
+        });
+
+        emit!(ProposalRejected {
+            wallet: wallet_config.key(),
+            proposal_id: proposal.id,
+            rejector,
+            reason,
+            timestamp: current_time,
+        });
 
-        if proposal.approvals.len() >= required_threshold as usize {
-            proposal.status = ProposalStatus::Approved;
-            msg!("Proposal {} approved with {} votes", proposal.key(), proposal.approvals.len());
+        // Reject once the remaining signers can no longer reach the threshold
+        // or the policy's rejection threshold is met; a rejection may also
+        // complete the quorum of an otherwise approved proposal
+        if !wallet_config.can_still_approve(proposal) || wallet_config.rejection_threshold_reached(proposal) {
+            proposal.set_status(ProposalStatus::Rejected);
+            wallet_config.record_proposal_finished();
+            msg!("Proposal {} rejected with {} votes", proposal.key(), proposal.rejections.len());
+        } else if wallet_config.approvals_needed(proposal) == 0
+            && wallet_config.quorum_reached(proposal)
+            && wallet_config.required_approvers_met(proposal)
+        {
+            let option = wallet_config.leading_option(proposal);
+            proposal.mark_approved(option, current_time);
+            msg!("Proposal {} reached quorum and is approved", proposal.key());
         } else {
-            msg!("Proposal {} approved by {}. {} more votes needed", 
-                 proposal.key(), approver, required_threshold - proposal.approvals.len() as u8);
+            msg!("Proposal {} rejected by {}", proposal.key(), rejector);
         }
 
         Ok(())
     }
 
-    /// Execute an approved proposal
-    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+    /// Abstain from a proposal
+    ///
+    /// An abstention counts toward the wallet's quorum but neither toward the
+    /// approval threshold nor toward rejecting the proposal.
+    pub fn abstain_proposal(ctx: Context<AbstainProposal>) -> Result<()> {
         let wallet_config = &ctx.accounts.wallet_config;
         let proposal = &mut ctx.accounts.proposal;
+
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+        require!(proposal.status == ProposalStatus::Pending, MultisigError::ProposalNotPending);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(proposal.expiration > current_time, MultisigError::ProposalExpired);
+
+        let voter = ctx.accounts.voter.key();
+        require!(wallet_config.signers.contains(&voter), MultisigError::NotAuthorized);
+        require!(wallet_config.is_active_member(&voter), MultisigError::MemberInactive);
+        require!(!proposal.has_voted(&voter), MultisigError::AlreadyVoted);
+
+        proposal.abstentions.push(voter);
+
+        emit!(ProposalAbstained {
+            wallet: wallet_config.key(),
+            proposal_id: proposal.id,
+            voter,
+            timestamp: current_time,
+        });
+
+        // An abstention may complete the quorum of an otherwise approved proposal
+        if wallet_config.approvals_needed(proposal) == 0
+            && wallet_config.quorum_reached(proposal)
+            && wallet_config.required_approvers_met(proposal)
+        {
+            let option = wallet_config.leading_option(proposal);
+            proposal.mark_approved(option, current_time);
+            msg!("Proposal {} reached quorum and is approved", proposal.key());
+        } else {
+            msg!("Proposal {} abstained by {}", proposal.key(), voter);
+        }
+
+        Ok(())
+    }
+
+    /// Execute an approved proposal
+    ///
+    /// Every account referenced by the proposal's instructions, including the
+    /// invoked programs, must be supplied through `remaining_accounts` in the
+    /// order the instructions first reference them. Instructions are always
+    /// invoked in their stored order.
+    ///
+    /// At most `max_instructions` instructions are invoked per call (0 for no
+    /// limit), resuming after those run by earlier calls, so large proposals can
+    /// be executed across several transactions. The proposal is marked
+    /// executed once its last instruction has run.
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
+        max_instructions: u16,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        let proposal = &mut ctx.accounts.proposal;
         
         require!(wallet_config.is_active, MultisigError::WalletInactive);
+        require!(proposal.wallet == wallet_config.key(), MultisigError::WalletMismatch);
         require!(proposal.status == ProposalStatus::Approved, MultisigError::ProposalNotApproved);
         
+        let executor = ctx.accounts.executor.key();
+        require!(wallet_config.signers.contains(&executor), MultisigError::NotAuthorized);
+        
         let current_time = Clock::get()?.unix_timestamp;
-        require!(proposal.expiration > current_time, MultisigError::ProposalExpired);
+        require!(
+            proposal.executable_until(wallet_config.policy.execution_grace_period) > current_time,
+            MultisigError::ProposalExpired
+        );
+        require!(proposal.is_active_at(current_time), MultisigError::ProposalNotYetActive);
+        require!(
+            proposal.timelock_elapsed(wallet_config.policy.execution_delay, current_time),
+            MultisigError::TimelockNotElapsed
+        );
+        require!(
+            proposal.voting_period_elapsed(wallet_config.policy.min_voting_period, current_time),
+            MultisigError::VotingPeriodNotElapsed
+        );
+        require!(
+            wallet_config.current_approvals_needed(proposal) == 0,
+            MultisigError::SignerSetChanged
+        );
+        require!(
+            wallet_config.fresh_approvals_needed(proposal, current_time) == 0,
+            MultisigError::ApprovalsStale
+        );
+        require!(
+            wallet_config.fresh_required_approvers_met(proposal, current_time),
+            MultisigError::MissingRequiredApprover
+        );
+
+        // Check the execution cooldown, the proposal's price condition and
+        // dependency and charge its transfers against the current spending
+        // window when execution starts; later calls resume an already started
+        // proposal
+        if proposal.executed_instruction_count == 0 {
+            require!(
+                wallet_config.execution_cooldown_elapsed(current_time),
+                MultisigError::ExecutionCooldownActive
+            );
+            wallet_config.last_execution_at = current_time;
 
-        // Execute the instructions
-        for _instruction in &proposal.instructions {
-            // This is a simplified execution - in a real implementation,
-            // you would need to handle different instruction types
-            msg!("Executing instruction for proposal {}", proposal.key());
+            if let Some(condition) = &proposal.price_condition {
+                let oracle = find_account_info(
+                    &condition.oracle,
+                    &wallet_config.to_account_info(),
+                    ctx.remaining_accounts,
+                )?;
+                require!(condition.is_met(&oracle)?, MultisigError::ConditionNotMet);
+            }
+            if let Some(dependency_id) = proposal.depends_on {
+                require!(
+                    proposal_executed(
+                        &wallet_config.to_account_info(),
+                        dependency_id,
+                        ctx.remaining_accounts,
+                    )?,
+                    MultisigError::DependencyNotExecuted
+                );
+                wallet_config.release_dependency(dependency_id);
+            }
+
+            if let ProposalCategory::TokenTransfer { amount, max_amount, .. } = &proposal.category {
+                require!(amount <= max_amount, MultisigError::AmountExceedsMax);
+            }
+
+            let amount = proposal.spend_amount().ok_or(MultisigError::SpendingLimitExceeded)?;
+            wallet_config.refresh_spending_window(current_time);
+            let spending_used = wallet_config
+                .spending_used
+                .checked_add(amount)
+                .and_then(|spending_used| spending_used.checked_add(wallet_config.policy.execution_bounty))
+                .ok_or(MultisigError::SpendingLimitExceeded)?;
+            require!(spending_used <= wallet_config.spending_limit, MultisigError::SpendingLimitExceeded);
+            wallet_config.spending_used = spending_used;
+
+            // Tagged proposals also draw on their tag's budget, if any
+            let spending_period = wallet_config.spending_period;
+            if let Some(budget) = wallet_config.budgets.iter_mut().find(|budget| budget.tag == proposal.tag) {
+                budget.charge(amount, spending_period, current_time)?;
+            }
+        }
+
+        // Execute the next run of instructions with the wallet PDA as signer
+        let instruction_count = proposal.executable_instructions().len();
+        let start = usize::from(proposal.executed_instruction_count);
+        let end = match max_instructions {
+            0 => instruction_count,
+            limit => instruction_count.min(start.saturating_add(usize::from(limit))),
+        };
+        let wallet_info = wallet_config.to_account_info();
+        check_instruction_order(
+            &proposal.executable_instructions()[start..end],
+            wallet_info.key,
+            ctx.remaining_accounts,
+        )?;
+
+        // Persist the executing flag before invoking, so an instruction that
+        // calls back into this program finds the wallet mid-execution and is
+        // rejected
+        wallet_config.executing = true;
+        wallet_config.exit(ctx.program_id)?;
+        let creator = wallet_config.creator;
+        let bump = [wallet_config.bump];
+        let signer_seeds: &[&[u8]] = &[b"wallet_config", creator.as_ref(), &bump];
+        invoke_wallet_instructions(
+            &proposal.executable_instructions()[start..end],
+            &wallet_info,
+            ctx.remaining_accounts,
+            signer_seeds,
+        )?;
+        wallet_config.executing = false;
+        proposal.executed_instruction_count = end as u16;
+
+        emit!(ExecutionAttempted {
+            wallet: wallet_config.key(),
+            proposal_id: proposal.id,
+            executor,
+            success: true,
+            error_code: None,
+            timestamp: current_time,
+        });
+
+        if end < instruction_count {
+            msg!("Proposal {} executed {} of {} instructions",
+                 proposal.key(), end, instruction_count);
+            return Ok(());
+        }
+
+        // Token transfer proposals move tokens out of the wallet's token account,
+        // clamped to the balance available at execution time. Both token
+        // accounts must hold the proposal's mint, and `transfer_checked`
+        // confirms the mint's decimals.
+        if let ProposalCategory::TokenTransfer { source, destination, mint, amount, .. } =
+            &proposal.category
+        {
+            let from = find_account_info(source, &wallet_info, ctx.remaining_accounts)?;
+            let to = find_account_info(destination, &wallet_info, ctx.remaining_accounts)?;
+            let mint_info = find_account_info(mint, &wallet_info, ctx.remaining_accounts)?;
+            let source_account: TokenAccount = load_token_state(&from)?;
+            let destination_account: TokenAccount = load_token_state(&to)?;
+            let mint_account: Mint = load_token_state(&mint_info)?;
+            require_keys_eq!(source_account.owner, wallet_info.key(), MultisigError::InvalidTokenOwner);
+            require_keys_eq!(source_account.mint, *mint, MultisigError::MintMismatch);
+            require_keys_eq!(destination_account.mint, *mint, MultisigError::MintMismatch);
+
+            let transfer_amount = (*amount).min(source_account.amount);
+            let transfer_accounts = TransferChecked {
+                from,
+                mint: mint_info,
+                to,
+                authority: wallet_info.clone(),
+            };
+            token::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    transfer_accounts,
+                    &[signer_seeds],
+                ),
+                transfer_amount,
+                mint_account.decimals,
+            )?;
+            msg!("Transferred {} of {} requested tokens from {} to {}",
+                 transfer_amount, amount, source, destination);
+        }
+
+        // SOL transfer proposals move lamports the wallet PDA holds above its
+        // rent-exempt minimum. The PDA carries data, so the System program
+        // can't debit it; as its owner, this program moves the lamports directly.
+        if let ProposalCategory::SolTransfer { recipient, lamports } = &proposal.category {
+            let to = find_account_info(recipient, &wallet_info, ctx.remaining_accounts)?;
+            transfer_wallet_lamports(&wallet_info, &to, *lamports)?;
+            msg!("Transferred {} lamports to {}", lamports, recipient);
+        }
+
+        // Signer change proposals replace the signer set and threshold
+        if let ProposalCategory::SignerChange { signers, threshold } = &proposal.category {
+            validate_signer_set(wallet_config, signers, *threshold)?;
+            wallet_config.replace_signers(signers.clone(), *threshold);
+            wallet_config.record_config_change(ConfigChangeKind::Signers, executor, current_time);
+            msg!("Signers and threshold updated by proposal {}", proposal.id);
+        }
+
+        // Spending limit change proposals set new limits, resetting the
+        // spending window as `set_spending_limits` does
+        if let ProposalCategory::SpendingLimitChange { limit, period } = &proposal.category {
+            wallet_config.spending_limit = *limit;
+            wallet_config.spending_period = *period;
+            wallet_config.spending_used = 0;
+            wallet_config.last_spending_reset = current_time;
+            wallet_config.record_config_change(ConfigChangeKind::SpendingLimits, executor, current_time);
+            msg!("Spending limits updated by proposal {}: {} per {} seconds", proposal.id, limit, period);
+        }
+
+        // Reward whoever completes the execution; the bounty was charged
+        // against the spending limit along with the proposal's transfers
+        let bounty = wallet_config.policy.execution_bounty;
+        if bounty > 0 {
+            transfer_wallet_lamports(&wallet_info, &ctx.accounts.executor.to_account_info(), bounty)?;
+            msg!("Paid an execution bounty of {} lamports to {}", bounty, executor);
         }
 
-        proposal.status = ProposalStatus::Executed;
+        proposal.set_status(ProposalStatus::Executed);
+        wallet_config.record_proposal_finished();
         proposal.executed_at = Some(current_time);
+        proposal.executed_by = Some(executor);
+
+        emit!(ProposalExecuted {
+            wallet: wallet_config.key(),
+            proposal_id: proposal.id,
+            executor,
+            timestamp: current_time,
+        });
         
         msg!("Proposal {} executed successfully", proposal.key());
         Ok(())
     }
 
-    /// Update signers and threshold (requires unanimous consent)
-    pub fn update_signers(
-        ctx: Context<UpdateSigners>,
-        new_signers: Vec<Pubkey>,
-        new_threshold: u8,
-    ) -> Result<()> {
-        let wallet_config = &mut ctx.accounts.wallet_config;
-        require!(wallet_config.is_active, MultisigError::WalletInactive);
-        require!(new_signers.len() >= new_threshold as usize, MultisigError::InvalidThreshold);
-        require!(new_threshold > 0, MultisigError::InvalidThreshold);
+    /// Record a failed attempt to execute an approved proposal (signers only)
+    ///
+    /// A failing instruction reverts the whole `execute_proposal`
+    /// transaction, so the failure can't be recorded there. Instead a signer
+    /// who saw the attempt fail reports its error code here, leaving a record
+    /// for operators diagnosing a stuck proposal.
+    pub fn record_execution_failure(ctx: Context<RecordExecutionFailure>, error_code: u32) -> Result<()> {
+        let wallet_config = &ctx.accounts.wallet_config;
+        let proposal = &mut ctx.accounts.proposal;
 
-        // Check if all current signers have approved this change
-        let approver = ctx.accounts.approver.key();
-        require!(wallet_config.signers.contains(&approver), MultisigError::NotAuthorized);
+        let reporter = ctx.accounts.reporter.key();
+        require!(wallet_config.signers.contains(&reporter), MultisigError::NotAuthorized);
+        require!(proposal.status == ProposalStatus::Approved, MultisigError::ProposalNotApproved);
 
-        // In a real implementation, you would track approvals for signer updates
-        // For now, we'll require the authority to make this change
-        require!(wallet_config.authority == approver, MultisigError::NotAuthorized);
+        proposal.failed_attempts = proposal.failed_attempts.saturating_add(1);
+        proposal.last_execution_error = Some(error_code);
 
-        wallet_config.signers = new_signers;
-        wallet_config.threshold = new_threshold;
+        emit!(ExecutionAttempted {
+            wallet: wallet_config.key(),
+            proposal_id: proposal.id,
+            executor: reporter,
+            success: false,
+            error_code: Some(error_code),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-        msg!("Signers and threshold updated");
+        msg!("Proposal {} failed to execute with error {} ({} failures)",
+             proposal.key(), error_code, proposal.failed_attempts);
         Ok(())
     }
 
-    /// Set spending limits
-    pub fn set_spending_limits(
-        ctx: Context<SetSpendingLimits>,
-        new_limit: u64,
-        new_period: i64,
-    ) -> Result<()> {
-        let wallet_config = &mut ctx.accounts.wallet_config;
-        require!(wallet_config.is_active, MultisigError::WalletInactive);
-        
-        let approver = ctx.accounts.approver.key();
-        require!(wallet_config.authority == approver, MultisigError::NotAuthorized);
+    /// Report whether a proposal could be executed right now
+    ///
+    /// True when the wallet is active, the proposal is approved, unexpired (or
+    /// within the policy's execution grace period) and past its timelock, enough of its approvals still count and are fresh, and its
+    /// transfers fit in the remaining spending limit. Nothing is
+    /// modified, so clients can simulate this and read the returned value.
+    pub fn is_executable(ctx: Context<IsExecutable>) -> Result<bool> {
+        let wallet_config = &ctx.accounts.wallet_config;
+        let proposal = &ctx.accounts.proposal;
+        let current_time = Clock::get()?.unix_timestamp;
 
-        wallet_config.spending_limit = new_limit;
-        wallet_config.spending_period = new_period;
-        wallet_config.spending_used = 0;
-        wallet_config.last_spending_reset = Clock::get()?.unix_timestamp;
+        let within_spending_limit = !wallet_config.preview_spending(proposal, current_time).exceeds_limit;
+        let executable = wallet_config.is_active
+            && proposal.status == ProposalStatus::Approved
+            && proposal.executable_until(wallet_config.policy.execution_grace_period) > current_time
+            && proposal.is_active_at(current_time)
+            && (proposal.executed_instruction_count > 0
+                || wallet_config.execution_cooldown_elapsed(current_time))
+            && proposal.timelock_elapsed(wallet_config.policy.execution_delay, current_time)
+            && proposal.voting_period_elapsed(wallet_config.policy.min_voting_period, current_time)
+            && wallet_config.current_approvals_needed(proposal) == 0
+            && wallet_config.fresh_approvals_needed(proposal, current_time) == 0
+            && wallet_config.fresh_required_approvers_met(proposal, current_time)
+            && within_spending_limit;
 
-        msg!("Spending limits updated: {} per {} seconds", new_limit, new_period);
-        Ok(())
+        msg!("Proposal {} executable: {}", proposal.key(), executable);
+        Ok(executable)
     }
 
-    /// Delegate voting power to another address
-    pub fn delegate_vote(
-        ctx: Context<DelegateVote>,
-        delegate: Pubkey,
-    ) -> Result<()> {
-        let wallet_config = &mut ctx.accounts.wallet_config;
-        require!(wallet_config.is_active, MultisigError::WalletInactive);
-        
-        let delegator = ctx.accounts.delegator.key();
-        require!(wallet_config.signers.contains(&delegator), MultisigError::NotAuthorized);
+    /// Report how executing a proposal now would affect the spending window
+    ///
+    /// Returns the proposal's total spend, the spending used it and any
+    /// execution bounty would leave in the current window and whether that
+    /// exceeds the limit or the budget
+    /// for the proposal's tag. Nothing is
+    /// modified, so signers can check this before approving.
+    pub fn preview_spending(ctx: Context<PreviewSpending>) -> Result<SpendingPreview> {
+        let wallet_config = &ctx.accounts.wallet_config;
+        let proposal = &ctx.accounts.proposal;
+        let current_time = Clock::get()?.unix_timestamp;
 
-        // Find and update the member's delegate
-        for member in &mut wallet_config.members {
-            if member.address == delegator {
-                member.delegate = Some(delegate);
-                msg!("Vote delegated from {} to {}", delegator, delegate);
-                return Ok(());
-            }
+        let preview = wallet_config.preview_spending(proposal, current_time);
+
+        msg!("Proposal {} would bring spending to {} of {}",
+             proposal.key(), preview.projected_spending_used, wallet_config.spending_limit);
+        Ok(preview)
+    }
+
+    /// Report a proposal's id, status, category, vote counts and expiration
+    ///
+    /// Lets clients list proposals by simulating this against each address
+    /// instead of fetching whole proposal accounts.
+    pub fn get_proposal_summary(ctx: Context<GetProposalSummary>) -> Result<ProposalSummary> {
+        let proposal = &ctx.accounts.proposal;
+
+        Ok(ProposalSummary {
+            id: proposal.id,
+            status: proposal.status.clone(),
+            category: proposal.category.clone(),
+            approvals: proposal.approvals.len() as u8,
+            rejections: proposal.rejections.len() as u8,
+            expiration: proposal.expiration,
+        })
+    }
+
+    /// Report the seconds left before a proposal expires by the on-chain
+    /// clock; negative once it has expired
+    pub fn time_until_expiry(ctx: Context<TimeUntilExpiry>) -> Result<i64> {
+        let proposal = &ctx.accounts.proposal;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let remaining = proposal.expiration.saturating_sub(current_time);
+
+        msg!("Proposal {} expires in {} seconds", proposal.key(), remaining);
+        Ok(remaining)
+    }
+
+    /// Drop approvals older than the wallet's approval validity window from a
+    /// pending or approved proposal. An approved proposal left short of its
+    /// threshold, quorum or required approvers goes back to pending. Anyone may call this, but
+    /// not once the proposal's execution has started.
+    pub fn compact_approvals(ctx: Context<CompactApprovals>) -> Result<()> {
+        let wallet_config = &ctx.accounts.wallet_config;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            matches!(proposal.status, ProposalStatus::Pending | ProposalStatus::Approved),
+            MultisigError::ProposalNotPending
+        );
+        // Re-approving would restart the timelock and let the rest of the run
+        // skip the checks made when execution started
+        require!(proposal.executed_instruction_count == 0, MultisigError::ExecutionInProgress);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let validity = wallet_config.policy.approval_validity;
+        let before = proposal.approvals.len();
+        proposal.approvals.retain(|approval| approval.is_fresh(validity, current_time));
+        let pruned = before.saturating_sub(proposal.approvals.len());
+
+        let still_approved = wallet_config.approvals_needed(proposal) == 0
+            && wallet_config.quorum_reached(proposal)
+            && wallet_config.required_approvers_met(proposal);
+        if proposal.status == ProposalStatus::Approved && !still_approved {
+            proposal.set_status(ProposalStatus::Pending);
+            proposal.approved_at = None;
+            proposal.selected_option = None;
+            msg!("Proposal {} returned to pending", proposal.key());
         }
 
-        Err(MultisigError::MemberNotFound.into())
+        msg!("Pruned {} stale approvals from proposal {}", pruned, proposal.key());
+        Ok(())
     }
 
-    /// Emergency override for urgent situations
-    pub fn emergency_override(
-        ctx: Context<EmergencyOverride>,
+    /// Mark a pending or approved proposal whose expiration has passed as
+    /// expired. Approved proposals only expire once the policy's execution
+    /// grace period has passed too. Anyone may call this.
+    pub fn expire_proposal(ctx: Context<ExpireProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let deadline = proposal
+            .expiry_deadline(ctx.accounts.wallet_config.policy.execution_grace_period)
+            .ok_or(MultisigError::ProposalNotPending)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(deadline <= current_time, MultisigError::ProposalNotExpired);
+
+        proposal.set_status(ProposalStatus::Expired);
+        ctx.accounts.wallet_config.record_proposal_finished();
+
+        msg!("Proposal {} expired", proposal.key());
+        Ok(())
+    }
+
+    /// Replace a pending proposal's instructions and description (original
+    /// proposer only, before anyone has approved it)
+    pub fn edit_proposal(
+        ctx: Context<EditProposal>,
         instructions: Vec<InstructionData>,
+        description: String,
     ) -> Result<()> {
         let wallet_config = &ctx.accounts.wallet_config;
-        require!(wallet_config.is_active, MultisigError::WalletInactive);
-        
-        let emergency_authority = ctx.accounts.emergency_authority.key();
-        require!(wallet_config.authority == emergency_authority, MultisigError::NotAuthorized);
+        let proposal = &mut ctx.accounts.proposal;
 
-        // Execute emergency instructions immediately
-        for _instruction in &instructions {
-            msg!("Executing emergency instruction");
+        let proposer = ctx.accounts.proposer.key();
+        require!(proposal.proposer == proposer, MultisigError::NotAuthorized);
+        if wallet_config.policy.allow_edits_after_approval {
+            // Approved proposals stay editable until execution starts
+            require!(
+                proposal.status == ProposalStatus::Pending
+                    || (proposal.status == ProposalStatus::Approved
+                        && proposal.executed_instruction_count == 0),
+                MultisigError::ProposalNotPending
+            );
+        } else {
+            require!(proposal.status == ProposalStatus::Pending, MultisigError::ProposalNotPending);
+            require!(proposal.approvals.is_empty(), MultisigError::ProposalHasApprovals);
         }
+        validate_description(&description)?;
+        let mut instructions = instructions;
+        validate_instructions(wallet_config, &mut instructions)?;
+        validate_options(&proposal.category, &proposal.options, instructions.len())?;
 
-        msg!("Emergency override executed by {}", emergency_authority);
+        let current_time = Clock::get()?.unix_timestamp;
+        proposal.instructions = instructions;
+        proposal.description = description;
+        proposal.created_at = current_time;
+        validate_proposal_size(proposal)?;
+
+        // Approvals were given for the old instructions, so signers must
+        // review the edited proposal again
+        if !proposal.approvals.is_empty() {
+            let cleared_approvals = proposal.approvals.len() as u8;
+            proposal.approvals.clear();
+            proposal.set_status(ProposalStatus::Pending);
+            proposal.approved_at = None;
+            proposal.selected_option = None;
+
+            emit!(ProposalReset {
+                wallet: wallet_config.key(),
+                proposal_id: proposal.id,
+                proposer,
+                cleared_approvals,
+                timestamp: current_time,
+            });
+        }
+
+        msg!("Proposal {} edited by {}", proposal.key(), proposer);
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct InitializeWallet<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + WalletConfig::INIT_SPACE,
-        seeds = [b"wallet_config", authority.key().as_ref()],
-        bump
+    /// Block a pending or approved proposal before it executes (guardian only)
+    pub fn veto_proposal(ctx: Context<VetoProposal>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        let proposal = &mut ctx.accounts.proposal;
+
+        let guardian = ctx.accounts.guardian.key();
+        require!(wallet_config.guardian == Some(guardian), MultisigError::GuardianOnly);
+        require!(
+            matches!(proposal.status, ProposalStatus::Pending | ProposalStatus::Approved),
+            MultisigError::ProposalNotPending
+        );
+        // Vetoing mid-run would leave the instructions already invoked
+        // applied and the rest unrunnable
+        require!(proposal.executed_instruction_count == 0, MultisigError::ExecutionInProgress);
+
+        proposal.set_status(ProposalStatus::Rejected);
+        wallet_config.record_proposal_finished();
+
+        let current_time = Clock::get()?.unix_timestamp;
+        emit!(ProposalVetoed {
+            wallet: wallet_config.key(),
+            proposal_id: proposal.id,
+            guardian,
+            timestamp: current_time,
+        });
+
+        msg!("Proposal {} vetoed by guardian {}", proposal.key(), guardian);
+        Ok(())
+    }
+
+    /// Cancel a pending proposal (original proposer only)
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+
+        let proposer = ctx.accounts.proposer.key();
+        require!(proposal.proposer == proposer, MultisigError::NotAuthorized);
+        require!(proposal.status == ProposalStatus::Pending, MultisigError::ProposalNotCancellable);
+
+        proposal.set_status(ProposalStatus::Cancelled);
+        ctx.accounts.wallet_config.record_proposal_finished();
+
+        msg!("Proposal {} cancelled by {}", proposal.key(), proposer);
+        Ok(())
+    }
+
+    /// Close a proposal in a terminal state, refunding rent to its fee payer
+    pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.status.is_terminal(), MultisigError::ProposalNotClosable);
+
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(!wallet_config.is_awaited(proposal.id), MultisigError::ProposalHasDependents);
+        wallet_config.release_unmet_dependency(proposal);
+        wallet_config.terminal_proposal_count = wallet_config.terminal_proposal_count.saturating_sub(1);
+
+        msg!("Proposal {} closed, rent returned to {}", proposal.key(), proposal.fee_payer);
+        Ok(())
+    }
+
+    /// Expire and clean up a batch of proposals. Anyone may call this.
+    ///
+    /// Proposals are passed as writable `remaining_accounts`. Pending or
+    /// approved ones past their deadline are marked expired, as with
+    /// `expire_proposal`; others are skipped. With `close_terminal`, proposals
+    /// in a terminal state are also closed, refunding rent to their fee payer
+    /// when it is passed as a writable remaining account too, unless an open
+    /// proposal still depends on them.
+    pub fn purge_expired<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PurgeExpired<'info>>,
+        close_terminal: bool,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        let grace_period = wallet_config.policy.execution_grace_period;
+        let current_time = Clock::get()?.unix_timestamp;
+        let mut expired = 0;
+        let mut closed = 0;
+
+        // Accounts not owned by this program are rent refund recipients
+        for info in ctx.remaining_accounts.iter().filter(|info| *info.owner == crate::ID) {
+            let mut proposal = Account::<Proposal>::try_from(info)?;
+            require!(proposal.wallet == wallet_config.key(), MultisigError::WalletMismatch);
+            require!(is_current_proposal(&proposal), MultisigError::UnsupportedAccountVersion);
+
+            let past_deadline = proposal
+                .expiry_deadline(grace_period)
+                .is_some_and(|deadline| deadline <= current_time);
+            if past_deadline {
+                proposal.set_status(ProposalStatus::Expired);
+                wallet_config.record_proposal_finished();
+                proposal.exit(&crate::ID)?;
+                expired += 1;
+            }
+
+            if close_terminal && proposal.status.is_terminal() && !wallet_config.is_awaited(proposal.id) {
+                let fee_payer = ctx.remaining_accounts.iter().find(|info| *info.key == proposal.fee_payer);
+                if let Some(fee_payer) = fee_payer {
+                    wallet_config.release_unmet_dependency(&proposal);
+                    proposal.close(fee_payer.clone())?;
+                    wallet_config.terminal_proposal_count =
+                        wallet_config.terminal_proposal_count.saturating_sub(1);
+                    closed += 1;
+                    continue;
+                }
+            }
+
+            if !past_deadline {
+                msg!("Skipping proposal {}", proposal.key());
+            }
+        }
+
+        msg!("Expired {} and closed {} of {} accounts", expired, closed, ctx.remaining_accounts.len());
+        Ok(())
+    }
+
+    /// Update signers and threshold (requires unanimous consent)
+    ///
+    /// Each current signer calls this with the same signer set and threshold;
+    /// the change is applied once every signer has approved it. Calling it
+    /// with a different set replaces the pending change and restarts approval.
+    /// Open proposals passed as writable `remaining_accounts` to the final
+    /// call lose removed signers' approvals if the policy says so.
+    pub fn update_signers<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateSigners<'info>>,
+        new_signers: Vec<Pubkey>,
+        new_threshold: u8,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+        validate_signer_set(wallet_config, &new_signers, new_threshold)?;
+
+        let approver = ctx.accounts.approver.key();
+        require!(wallet_config.signers.contains(&approver), MultisigError::NotAuthorized);
+
+        let mut change = match wallet_config.pending_signer_change.take() {
+            Some(change) if change.signers == new_signers && change.threshold == new_threshold => change,
+            _ => SignerChange {
+                signers: new_signers,
+                threshold: new_threshold,
+                approvals: Vec::new(),
+            },
+        };
+        require!(!change.approvals.contains(&approver), MultisigError::AlreadyApproved);
+        change.approvals.push(approver);
+
+        // Check if all current signers have approved this change
+        if change.approvals.len() < wallet_config.signers.len() {
+            msg!("Signer change approved by {}. {} more approvals needed",
+                 approver, wallet_config.signers.len().saturating_sub(change.approvals.len()));
+            wallet_config.pending_signer_change = Some(change);
+            return Ok(());
+        }
+
+        wallet_config.replace_signers(change.signers, change.threshold);
+        wallet_config.record_config_change(
+            ConfigChangeKind::Signers,
+            approver,
+            Clock::get()?.unix_timestamp,
+        );
+        invalidate_removed_approvals(wallet_config, ctx.remaining_accounts)?;
+
+        msg!("Signers and threshold updated");
+        Ok(())
+    }
+
+    /// Start or support recovery to a replacement signer set
+    ///
+    /// For wallets that lost too many signer keys to reach their threshold.
+    /// The first call records the proposed set; further signers back it by
+    /// calling with the same set. A different set restarts recovery.
+    pub fn initiate_recovery(
+        ctx: Context<InitiateRecovery>,
+        new_signers: Vec<Pubkey>,
+        new_threshold: u8,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+        require!(wallet_config.policy.recovery_threshold > 0, MultisigError::RecoveryDisabled);
+        validate_signer_set(wallet_config, &new_signers, new_threshold)?;
+
+        let signer = ctx.accounts.signer.key();
+        require!(wallet_config.signers.contains(&signer), MultisigError::NotAuthorized);
+
+        let mut recovery = match wallet_config.pending_recovery.take() {
+            Some(recovery) if recovery.signers == new_signers && recovery.threshold == new_threshold => recovery,
+            _ => Recovery {
+                signers: new_signers,
+                threshold: new_threshold,
+                initiated_at: Clock::get()?.unix_timestamp,
+                approvals: Vec::new(),
+            },
+        };
+        require!(!recovery.approvals.contains(&signer), MultisigError::AlreadyApproved);
+        recovery.approvals.push(signer);
+
+        msg!("Recovery backed by {} ({} of {} needed)",
+             signer, recovery.approvals.len(), wallet_config.policy.recovery_threshold);
+        wallet_config.pending_recovery = Some(recovery);
+        Ok(())
+    }
+
+    /// Apply a recovery once enough signers back it and the recovery delay
+    /// has passed since it was initiated
+    pub fn complete_recovery(ctx: Context<CompleteRecovery>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let signer = ctx.accounts.signer.key();
+        require!(wallet_config.signers.contains(&signer), MultisigError::NotAuthorized);
+
+        let recovery = wallet_config
+            .pending_recovery
+            .take()
+            .ok_or(MultisigError::NoRecoveryPending)?;
+        require!(
+            recovery.approvals.len() >= wallet_config.policy.recovery_threshold as usize,
+            MultisigError::RecoveryNotApproved
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time >= recovery.initiated_at.saturating_add(wallet_config.policy.recovery_delay),
+            MultisigError::RecoveryNotElapsed
+        );
+
+        wallet_config.replace_signers(recovery.signers, recovery.threshold);
+        wallet_config.record_config_change(ConfigChangeKind::Signers, signer, current_time);
+
+        msg!("Wallet recovered with {} signers and threshold {}",
+             wallet_config.signers.len(), wallet_config.threshold);
+        Ok(())
+    }
+
+    /// Abandon a pending recovery (authority or admins)
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+
+        let admin = ctx.accounts.admin.key();
+        require!(wallet_config.is_admin(&admin), MultisigError::NotAuthorized);
+        require!(wallet_config.pending_recovery.is_some(), MultisigError::NoRecoveryPending);
+
+        wallet_config.pending_recovery = None;
+
+        msg!("Recovery cancelled by {}", admin);
+        Ok(())
+    }
+
+    /// Set spending limits immediately, resetting the spending window. Only
+    /// allowed while the policy has no spending limit delay; otherwise use
+    /// `propose_spending_limit`. Increases need a `SpendingLimitChange`
+    /// proposal instead when the policy governs them.
+    pub fn set_spending_limits(
+        ctx: Context<SetSpendingLimits>,
+        new_limit: u64,
+        new_period: i64,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+        
+        let approver = ctx.accounts.approver.key();
+        require!(wallet_config.authority == approver, MultisigError::NotAuthorized);
+        require!(
+            wallet_config.policy.spending_limit_delay == 0,
+            MultisigError::SpendingLimitChangeTimelocked
+        );
+        require!(
+            !wallet_config.policy.govern_limit_increases
+                || !wallet_config.is_spending_increase(new_limit, new_period),
+            MultisigError::SpendingLimitIncreaseRequiresProposal
+        );
+
+        wallet_config.spending_limit = new_limit;
+        wallet_config.spending_period = new_period;
+        wallet_config.spending_used = 0;
+        let current_time = Clock::get()?.unix_timestamp;
+        wallet_config.last_spending_reset = current_time;
+        wallet_config.record_config_change(ConfigChangeKind::SpendingLimits, approver, current_time);
+
+        msg!("Spending limits updated: {} per {} seconds", new_limit, new_period);
+        Ok(())
+    }
+
+    /// Propose new spending limits that `apply_spending_limit` can commit once
+    /// the policy's spending limit delay has passed (authority only).
+    /// Replaces any change already pending. The spending window is only reset
+    /// on apply when `reset_spending` is set.
+    pub fn propose_spending_limit(
+        ctx: Context<SetSpendingLimits>,
+        new_limit: u64,
+        new_period: i64,
+        reset_spending: bool,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let approver = ctx.accounts.approver.key();
+        require!(wallet_config.authority == approver, MultisigError::NotAuthorized);
+        require!(new_limit > 0, MultisigError::InvalidSpendingLimit);
+        require!(
+            !wallet_config.policy.govern_limit_increases
+                || !wallet_config.is_spending_increase(new_limit, new_period),
+            MultisigError::SpendingLimitIncreaseRequiresProposal
+        );
+
+        let effective_at = Clock::get()?
+            .unix_timestamp
+            .saturating_add(wallet_config.policy.spending_limit_delay);
+        wallet_config.pending_spending_limit = Some(PendingSpendingLimit {
+            limit: new_limit,
+            period: new_period,
+            reset_spending,
+            effective_at,
+        });
+
+        msg!("Spending limits of {} per {} seconds proposed, effective at {}",
+             new_limit, new_period, effective_at);
+        Ok(())
+    }
+
+    /// Commit the pending spending limit change once its delay has passed
+    /// (authority only)
+    pub fn apply_spending_limit(ctx: Context<SetSpendingLimits>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let approver = ctx.accounts.approver.key();
+        require!(wallet_config.authority == approver, MultisigError::NotAuthorized);
+
+        let pending = wallet_config
+            .pending_spending_limit
+            .clone()
+            .ok_or(MultisigError::NoSpendingLimitChangePending)?;
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(current_time >= pending.effective_at, MultisigError::SpendingLimitDelayNotElapsed);
+
+        wallet_config.spending_limit = pending.limit;
+        wallet_config.spending_period = pending.period;
+        if pending.reset_spending {
+            wallet_config.spending_used = 0;
+            wallet_config.last_spending_reset = current_time;
+        }
+        wallet_config.pending_spending_limit = None;
+        wallet_config.record_config_change(ConfigChangeKind::SpendingLimits, approver, current_time);
+
+        msg!("Spending limits updated: {} per {} seconds", pending.limit, pending.period);
+        Ok(())
+    }
+
+    /// Set the spending budget for proposals carrying `tag` (authority only).
+    /// Budgets share the wallet's spending period and apply on top of the
+    /// overall spending limit; a limit of 0 removes the budget.
+    pub fn set_budget(ctx: Context<SetSpendingLimits>, tag: [u8; 16], limit: u64) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let approver = ctx.accounts.approver.key();
+        require!(wallet_config.authority == approver, MultisigError::NotAuthorized);
+
+        wallet_config.budgets.retain(|budget| budget.tag != tag);
+        if limit > 0 {
+            require!(wallet_config.budgets.len() < MAX_BUDGETS, MultisigError::TooManyBudgets);
+            wallet_config.budgets.push(Budget {
+                tag,
+                limit,
+                used: 0,
+                last_reset: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        msg!("Budget set to {} per {} seconds by {}", limit, wallet_config.spending_period, approver);
+        Ok(())
+    }
+
+    /// Start a new spending window once the current one has elapsed
+    /// (authority or treasurers)
+    pub fn reset_spending_window(ctx: Context<ResetSpendingWindow>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let caller = ctx.accounts.caller.key();
+        require!(wallet_config.is_treasurer(&caller), MultisigError::NotAuthorized);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            wallet_config.spending_window_elapsed(current_time),
+            MultisigError::SpendingPeriodNotElapsed
+        );
+        wallet_config.refresh_spending_window(current_time);
+
+        msg!("Spending window reset by {}", caller);
+        Ok(())
+    }
+
+    /// Report how much can still be spent in the current spending window
+    pub fn spending_remaining(ctx: Context<SpendingRemaining>) -> Result<u64> {
+        let wallet_config = &ctx.accounts.wallet_config;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let remaining = wallet_config
+            .spending_limit
+            .saturating_sub(wallet_config.current_spending_used(current_time));
+
+        msg!("Spending remaining: {}", remaining);
+        Ok(remaining)
+    }
+
+    /// Nominate a new wallet authority (authority only)
+    ///
+    /// The nominee takes over only once it calls `accept_authority`.
+    pub fn transfer_authority(ctx: Context<TransferAuthority>, new_authority: Pubkey) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let authority = ctx.accounts.authority.key();
+        require!(wallet_config.authority == authority, MultisigError::NotAuthorized);
+        require!(
+            wallet_config.pending_authority.is_none(),
+            MultisigError::AuthorityTransferPending
+        );
+
+        wallet_config.pending_authority = Some(new_authority);
+
+        msg!("Authority transfer to {} proposed by {}", new_authority, authority);
+        Ok(())
+    }
+
+    /// Withdraw a pending authority nomination (authority only)
+    pub fn cancel_authority_transfer(ctx: Context<TransferAuthority>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+
+        let authority = ctx.accounts.authority.key();
+        require!(wallet_config.authority == authority, MultisigError::NotAuthorized);
+        require!(
+            wallet_config.pending_authority.is_some(),
+            MultisigError::NoAuthorityTransferPending
+        );
+
+        wallet_config.pending_authority = None;
+
+        msg!("Authority transfer cancelled by {}", authority);
+        Ok(())
+    }
+
+    /// Accept a pending authority nomination (nominee only)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let new_authority = ctx.accounts.new_authority.key();
+        let pending_authority = wallet_config
+            .pending_authority
+            .ok_or(MultisigError::NoAuthorityTransferPending)?;
+        require!(pending_authority == new_authority, MultisigError::NotAuthorized);
+
+        let previous_authority = wallet_config.authority;
+        wallet_config.authority = new_authority;
+        wallet_config.pending_authority = None;
+        wallet_config.refresh_authority_is_signer();
+
+        msg!("Authority transferred from {} to {}", previous_authority, new_authority);
+        Ok(())
+    }
+
+    /// Appoint or remove the wallet's guardian (authority only)
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Option<Pubkey>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let authority = ctx.accounts.authority.key();
+        require!(wallet_config.authority == authority, MultisigError::NotAuthorized);
+
+        wallet_config.guardian = guardian;
+
+        msg!("Guardian set to {:?}", guardian);
+        Ok(())
+    }
+
+    /// Switch between count-based, weighted and proportional voting
+    /// (authority only)
+    ///
+    /// In weighted mode a proposal is approved once the summed weight of its
+    /// approvers reaches `weight_threshold`, whatever its category. In
+    /// proportional mode it needs `threshold_bps` basis points of the current
+    /// signers, rounded up.
+    pub fn set_voting_mode(
+        ctx: Context<SetVotingMode>,
+        voting_mode: VotingMode,
+        weight_threshold: u64,
+        threshold_bps: u16,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let authority = ctx.accounts.authority.key();
+        require!(wallet_config.authority == authority, MultisigError::NotAuthorized);
+
+        validate_voting_mode(
+            &voting_mode,
+            weight_threshold,
+            threshold_bps,
+            wallet_config.total_weight(&wallet_config.signers),
+        )?;
+
+        wallet_config.voting_mode = voting_mode;
+        wallet_config.weight_threshold = weight_threshold;
+        wallet_config.threshold_bps = threshold_bps;
+
+        msg!("Voting mode updated, weight threshold {}, threshold bps {}", weight_threshold, threshold_bps);
+        Ok(())
+    }
+
+    /// Replace the wallet's approval and execution policy (authority only)
+    pub fn set_policy(ctx: Context<SetPolicy>, policy: WalletPolicy) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let authority = ctx.accounts.authority.key();
+        require!(wallet_config.authority == authority, MultisigError::NotAuthorized);
+        validate_policy(&policy, wallet_config.signers.len())?;
+
+        wallet_config.policy = policy;
+        require!(wallet_config.thresholds_reachable(), MultisigError::InvalidThreshold);
+
+        msg!("Wallet policy updated by {}", authority);
+        Ok(())
+    }
+
+    /// Add a member (and signer) with the given role (authority or admins only)
+    pub fn add_member(
+        ctx: Context<AddMember>,
+        address: Pubkey,
+        role: MemberRole,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let admin = ctx.accounts.admin.key();
+        require!(wallet_config.is_admin(&admin), MultisigError::NotAuthorized);
+
+        wallet_config.check_can_add_signer(&address)?;
+
+        wallet_config.add_signer(address, role);
+        wallet_config.record_config_change(
+            ConfigChangeKind::MemberAdded,
+            admin,
+            Clock::get()?.unix_timestamp,
+        );
+
+        msg!("Member {} added by {}", address, admin);
+        Ok(())
+    }
+
+    /// Nominate a new member (and signer) with the given role (authority or
+    /// admins only). The nominee is only added once it accepts with
+    /// `accept_signer_role`, proving it controls the key.
+    pub fn nominate_signer(
+        ctx: Context<AddMember>,
+        address: Pubkey,
+        role: MemberRole,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let admin = ctx.accounts.admin.key();
+        require!(wallet_config.is_admin(&admin), MultisigError::NotAuthorized);
+        require!(
+            wallet_config.pending_signer.is_none(),
+            MultisigError::SignerNominationPending
+        );
+        wallet_config.check_can_add_signer(&address)?;
+
+        wallet_config.pending_signer = Some(PendingSigner { address, role });
+
+        msg!("Signer {} nominated by {}", address, admin);
+        Ok(())
+    }
+
+    /// Withdraw a pending signer nomination (authority or admins only)
+    pub fn cancel_signer_nomination(ctx: Context<AddMember>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+
+        let admin = ctx.accounts.admin.key();
+        require!(wallet_config.is_admin(&admin), MultisigError::NotAuthorized);
+        require!(
+            wallet_config.pending_signer.is_some(),
+            MultisigError::NoSignerNominationPending
+        );
+
+        wallet_config.pending_signer = None;
+
+        msg!("Signer nomination cancelled by {}", admin);
+        Ok(())
+    }
+
+    /// Accept a pending signer nomination (nominee only), joining the wallet
+    /// as a member and signer with the nominated role
+    pub fn accept_signer_role(ctx: Context<AcceptSignerRole>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let nominee = ctx.accounts.nominee.key();
+        let pending_signer = wallet_config
+            .pending_signer
+            .clone()
+            .ok_or(MultisigError::NoSignerNominationPending)?;
+        require!(pending_signer.address == nominee, MultisigError::NotAuthorized);
+
+        // The signer set may have changed since the nomination
+        wallet_config.check_can_add_signer(&nominee)?;
+        wallet_config.pending_signer = None;
+        wallet_config.add_signer(nominee, pending_signer.role);
+        wallet_config.record_config_change(
+            ConfigChangeKind::MemberAdded,
+            nominee,
+            Clock::get()?.unix_timestamp,
+        );
+
+        msg!("Signer {} accepted its nomination", nominee);
+        Ok(())
+    }
+
+    /// Remove a member (and signer) (authority or admins only)
+    ///
+    /// Open proposals passed as writable `remaining_accounts` lose the
+    /// member's approvals if the policy says so.
+    pub fn remove_member<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveMember<'info>>,
+        address: Pubkey,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let admin = ctx.accounts.admin.key();
+        require!(wallet_config.is_admin(&admin), MultisigError::NotAuthorized);
+
+        let index = wallet_config
+            .members
+            .iter()
+            .position(|member| member.address == address)
+            .ok_or(MultisigError::MemberNotFound)?;
+        wallet_config.members.remove(index);
+        wallet_config.signers.retain(|signer| *signer != address);
+
+        // The remaining active signers must still be able to meet the threshold
+        require!(
+            wallet_config.active_signer_count() >= wallet_config.max_threshold() as usize,
+            MultisigError::InsufficientSigners
+        );
+
+        // Nobody can keep voting through the removed member
+        for member in &mut wallet_config.members {
+            if member.delegate == Some(address) {
+                member.delegate = None;
+            }
+        }
+
+        // Approvals gathered for the old signer set no longer apply
+        wallet_config.pending_signer_change = None;
+        wallet_config.record_signer_change();
+        wallet_config.record_config_change(
+            ConfigChangeKind::MemberRemoved,
+            admin,
+            Clock::get()?.unix_timestamp,
+        );
+        invalidate_removed_approvals(wallet_config, ctx.remaining_accounts)?;
+
+        msg!("Member {} removed by {}", address, admin);
+        Ok(())
+    }
+
+    /// Set a member's voting weight (authority or admins)
+    pub fn set_member_weight(
+        ctx: Context<SetMemberWeight>,
+        address: Pubkey,
+        weight: u64,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let admin = ctx.accounts.admin.key();
+        require!(wallet_config.is_admin(&admin), MultisigError::NotAuthorized);
+
+        let member = wallet_config
+            .members
+            .iter_mut()
+            .find(|member| member.address == address)
+            .ok_or(MultisigError::MemberNotFound)?;
+        member.weight = weight;
+
+        msg!("Member {} weight set to {} by {}", address, weight, admin);
+        Ok(())
+    }
+
+    /// Activate or deactivate a member without removing it (authority or
+    /// admins)
+    ///
+    /// A deactivated member keeps its role, weight and history but can
+    /// neither submit proposals nor approve them, and approvals it already
+    /// gave stop counting toward thresholds until it is reactivated.
+    pub fn set_member_active(
+        ctx: Context<SetMemberActive>,
+        address: Pubkey,
+        active: bool,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let admin = ctx.accounts.admin.key();
+        require!(wallet_config.is_admin(&admin), MultisigError::NotAuthorized);
+
+        let member = wallet_config
+            .members
+            .iter_mut()
+            .find(|member| member.address == address)
+            .ok_or(MultisigError::MemberNotFound)?;
+        member.is_active = active;
+
+        // The active signers must still be able to meet the threshold
+        require!(
+            wallet_config.active_signer_count() >= wallet_config.max_threshold() as usize,
+            MultisigError::InsufficientSigners
+        );
+
+        msg!("Member {} active set to {} by {}", address, active, admin);
+        Ok(())
+    }
+
+    /// Delegate voting power to another address
+    pub fn delegate_vote(
+        ctx: Context<DelegateVote>,
+        delegate: Pubkey,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+        
+        let delegator = ctx.accounts.delegator.key();
+        require!(wallet_config.signers.contains(&delegator), MultisigError::NotAuthorized);
+        require!(delegate != delegator, MultisigError::SelfDelegation);
+        // A signer already votes directly; voting for another signer as well
+        // would give it two votes
+        require!(!wallet_config.signers.contains(&delegate), MultisigError::DelegateIsSigner);
+
+        // Find and update the member's delegate
+        for member in &mut wallet_config.members {
+            if member.address == delegator {
+                member.delegate = Some(delegate);
+                msg!("Vote delegated from {} to {}", delegator, delegate);
+                return Ok(());
+            }
+        }
+
+        Err(MultisigError::MemberNotFound.into())
+    }
+
+    /// Revoke a vote delegation, returning to direct voting
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let delegator = ctx.accounts.delegator.key();
+
+        // Find and clear the member's delegate
+        for member in &mut wallet_config.members {
+            if member.address == delegator {
+                member.delegate = None;
+                msg!("Vote delegation revoked by {}", delegator);
+                return Ok(());
+            }
+        }
+
+        Err(MultisigError::MemberNotFound.into())
+    }
+
+    /// Report whether `address` is a signer, its membership details and who
+    /// votes for it
+    pub fn member_status(ctx: Context<GetMemberStatus>, address: Pubkey) -> Result<MemberStatus> {
+        let wallet_config = &ctx.accounts.wallet_config;
+
+        let status = wallet_config.member_status(&address);
+
+        msg!("Member {} votes through {:?}", address, status.effective_voter);
+        Ok(status)
+    }
+
+    /// Report the wallet's violated invariants as a bitmask of `INVARIANT_*`
+    /// flags, 0 when healthy. Nothing is modified. With `fail_on_critical`,
+    /// fails instead when the threshold or membership invariants are broken.
+    pub fn check_invariants(ctx: Context<CheckInvariants>, fail_on_critical: bool) -> Result<u8> {
+        let wallet_config = &ctx.accounts.wallet_config;
+
+        let violations = wallet_config.invariant_violations(Clock::get()?.unix_timestamp);
+        require!(
+            !fail_on_critical || violations & (INVARIANT_THRESHOLD | INVARIANT_MEMBERS) == 0,
+            MultisigError::InvariantViolated
+        );
+
+        msg!("Wallet {} invariant violations: {:#06b}", wallet_config.key(), violations);
+        Ok(violations)
+    }
+
+    /// Upgrade a wallet config written by an older program version to
+    /// `WALLET_CONFIG_VERSION`. The account is reallocated to the current
+    /// layout, with the authority covering any extra rent, and the fields
+    /// added since its version get their defaults. Does nothing to a current
+    /// account.
+    pub fn migrate_wallet_config(ctx: Context<MigrateWalletConfig>) -> Result<()> {
+        let info = ctx.accounts.wallet_config.to_account_info();
+
+        // A supported version, checked by `MigrateWalletConfig`
+        let version = info.try_borrow_data()?[WALLET_CONFIG_VERSION_OFFSET];
+        if version == WALLET_CONFIG_VERSION {
+            msg!("Wallet {} is already at version {}", info.key(), version);
+            return Ok(());
+        }
+
+        let space = 8 + WalletConfig::INIT_SPACE;
+        let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+        if shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        info.resize(space)?;
+
+        // An older account reads as the current layout now that it has room
+        // for the appended fields. Those bytes may be stale, so each field
+        // appended since `version` must be given its default here.
+        let mut wallet_config = WalletConfig::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(!wallet_config.executing, MultisigError::ReentrantCall);
+        if version < 2 {
+            // Proposals created before version 2 didn't register their
+            // dependencies
+            wallet_config.awaited_dependencies = [0; MAX_AWAITED_DEPENDENCIES];
+        }
+        wallet_config.version = WALLET_CONFIG_VERSION;
+        wallet_config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!("Wallet {} migrated from version {} to {}", info.key(), version, WALLET_CONFIG_VERSION);
+        Ok(())
+    }
+
+    /// Pause the wallet, blocking proposals, votes and execution
+    pub fn pause_wallet(ctx: Context<PauseWallet>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+
+        let admin = ctx.accounts.admin.key();
+        require!(wallet_config.is_admin(&admin), MultisigError::NotAuthorized);
+
+        wallet_config.is_active = false;
+
+        msg!("Wallet paused by {}", admin);
+        Ok(())
+    }
+
+    /// Resume a paused wallet
+    pub fn resume_wallet(ctx: Context<ResumeWallet>) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(!wallet_config.is_active, MultisigError::WalletAlreadyActive);
+
+        let admin = ctx.accounts.admin.key();
+        require!(wallet_config.is_admin(&admin), MultisigError::NotAuthorized);
+
+        wallet_config.is_active = true;
+
+        msg!("Wallet resumed by {}", admin);
+        Ok(())
+    }
+
+    /// Emergency override for urgent situations
+    ///
+    /// Instructions are invoked immediately with the wallet PDA as signer, so
+    /// their accounts must be supplied through `remaining_accounts`. Every
+    /// override is recorded in the wallet's emergency log.
+    pub fn emergency_override<'info>(
+        ctx: Context<'_, '_, '_, 'info, EmergencyOverride<'info>>,
+        instructions: Vec<InstructionData>,
+    ) -> Result<()> {
+        let wallet_config = &mut ctx.accounts.wallet_config;
+        require!(wallet_config.is_active, MultisigError::WalletInactive);
+        
+        let emergency_authority = ctx.accounts.emergency_authority.key();
+        require!(wallet_config.authority == emergency_authority, MultisigError::NotAuthorized);
+
+        // Execute emergency instructions immediately, guarded against
+        // re-entrant calls like proposal execution
+        let wallet_info = wallet_config.to_account_info();
+        wallet_config.executing = true;
+        wallet_config.exit(ctx.program_id)?;
+        let bump = [wallet_config.bump];
+        let signer_seeds: &[&[u8]] = &[b"wallet_config", wallet_config.creator.as_ref(), &bump];
+        invoke_wallet_instructions(
+            &instructions,
+            &wallet_info,
+            ctx.remaining_accounts,
+            signer_seeds,
+        )?;
+        wallet_config.executing = false;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let instruction_count = u8::try_from(instructions.len()).unwrap_or(u8::MAX);
+        wallet_config.record_emergency(EmergencyLog {
+            timestamp: current_time,
+            authority: emergency_authority,
+            instruction_count,
+        });
+
+        emit!(EmergencyOverrideExecuted {
+            wallet: wallet_config.key(),
+            authority: emergency_authority,
+            instruction_count,
+            timestamp: current_time,
+        });
+
+        msg!("Emergency override executed by {}", emergency_authority);
+        Ok(())
+    }
+}
+
+/// Whether any key appears more than once in `keys`
+fn has_duplicates(keys: &[Pubkey]) -> bool {
+    keys.iter()
+        .enumerate()
+        .any(|(i, key)| keys[i + 1..].contains(key))
+}
+
+/// Role of `pubkey` in the wallet, if it is a member
+fn member_role(wallet_config: &WalletConfig, pubkey: &Pubkey) -> Option<MemberRole> {
+    wallet_config.member(pubkey).map(|member| member.role.clone())
+}
+
+/// Invoke `instructions` in order with the wallet PDA as signer. The accounts
+/// of every instruction are resolved up front so nothing is invoked unless
+/// all of them can run.
+fn invoke_wallet_instructions<'info>(
+    instructions: &[InstructionData],
+    wallet_info: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let mut resolved = Vec::with_capacity(instructions.len());
+    for instruction in instructions {
+        resolved.push(instruction_account_infos(instruction, wallet_info, remaining_accounts)?);
+    }
+
+    for (instruction, account_infos) in instructions.iter().zip(resolved.iter()) {
+        invoke_signed(&instruction.to_instruction(), account_infos, &[signer_seeds])?;
+        msg!("Executed instruction for program {}", instruction.program_id);
+    }
+    Ok(())
+}
+
+/// Check that `remaining_accounts` supplies the accounts of `instructions`,
+/// each followed by its program, in the order the instructions first
+/// reference them. Accounts the instructions don't use may appear anywhere.
+fn check_instruction_order(
+    instructions: &[InstructionData],
+    wallet_key: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    let mut seen: Vec<&Pubkey> = Vec::new();
+    let mut last_position = 0;
+    for instruction in instructions {
+        let keys = instruction
+            .accounts
+            .iter()
+            .map(|meta| &meta.pubkey)
+            .chain(std::iter::once(&instruction.program_id));
+        for key in keys {
+            if key == wallet_key || seen.contains(&key) {
+                continue;
+            }
+            let position = remaining_accounts
+                .iter()
+                .position(|info| info.key == key)
+                .ok_or(MultisigError::MissingInstructionAccount)?;
+            require!(position >= last_position, MultisigError::InstructionOrderMismatch);
+            last_position = position;
+            seen.push(key);
+        }
+    }
+    Ok(())
+}
+
+/// Collect the account infos needed to invoke `instruction`, failing if any
+/// referenced account (or the program itself) was not supplied by the caller
+fn instruction_account_infos<'info>(
+    instruction: &InstructionData,
+    wallet_info: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<Vec<AccountInfo<'info>>> {
+    let mut account_infos = Vec::with_capacity(instruction.accounts.len() + 1);
+    for meta in &instruction.accounts {
+        account_infos.push(find_account_info(&meta.pubkey, wallet_info, remaining_accounts)?);
+    }
+    account_infos.push(find_account_info(&instruction.program_id, wallet_info, remaining_accounts)?);
+    Ok(account_infos)
+}
+
+/// Check a replacement signer set is well formed and can still meet every
+/// category threshold
+fn validate_signer_set(wallet_config: &WalletConfig, signers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(threshold > 0, MultisigError::ThresholdIsZero);
+    require!(signers.len() >= threshold as usize, MultisigError::ThresholdExceedsSigners);
+    require!(signers.len() <= MAX_SIGNERS, MultisigError::TooManySigners);
+    require!(!has_duplicates(signers), MultisigError::DuplicateSigner);
+    require!(
+        signers.len() >= wallet_config.admin_threshold as usize,
+        MultisigError::UnachievableAdminThreshold
+    );
+    require!(
+        signers.len() >= wallet_config.emergency_threshold as usize,
+        MultisigError::InvalidThreshold
+    );
+    require!(signers.len() >= wallet_config.quorum as usize, MultisigError::InvalidQuorum);
+    Ok(())
+}
+
+/// Check policy durations are not negative and the recovery and rejection
+/// thresholds are reachable by `signer_count` signers
+fn validate_policy(policy: &WalletPolicy, signer_count: usize) -> Result<()> {
+    require!(policy.execution_delay >= 0, MultisigError::InvalidTimeout);
+    require!(policy.recovery_delay >= 0, MultisigError::InvalidTimeout);
+    require!(policy.approval_validity >= 0, MultisigError::InvalidTimeout);
+    require!(policy.min_proposal_interval >= 0, MultisigError::InvalidTimeout);
+    require!(policy.min_execution_interval >= 0, MultisigError::InvalidTimeout);
+    require!(policy.spending_limit_delay >= 0, MultisigError::InvalidTimeout);
+    require!(policy.execution_grace_period >= 0, MultisigError::InvalidTimeout);
+    require!(policy.min_voting_period >= 0, MultisigError::InvalidTimeout);
+    require!(
+        policy.execution_bounty <= MAX_EXECUTION_BOUNTY,
+        MultisigError::ExecutionBountyTooLarge
+    );
+    require!(
+        policy.recovery_threshold as usize <= signer_count,
+        MultisigError::InvalidThreshold
+    );
+    require!(
+        policy.rejection_threshold as usize <= signer_count,
+        MultisigError::InvalidThreshold
+    );
+    Ok(())
+}
+
+/// Check weighted mode has a weight threshold the signers' `total_weight`
+/// can reach and proportional mode a share between 0 and 100%
+fn validate_voting_mode(
+    voting_mode: &VotingMode,
+    weight_threshold: u64,
+    threshold_bps: u16,
+    total_weight: u64,
+) -> Result<()> {
+    if *voting_mode == VotingMode::Weighted {
+        require!(weight_threshold > 0, MultisigError::InvalidThreshold);
+        require!(weight_threshold <= total_weight, MultisigError::InvalidThreshold);
+    }
+    if *voting_mode == VotingMode::Proportional {
+        require!(
+            threshold_bps > 0 && threshold_bps <= BPS_DENOMINATOR,
+            MultisigError::InvalidThreshold
+        );
+    }
+    Ok(())
+}
+
+/// Set up a newly created wallet from `snapshot`, checking it describes a
+/// usable wallet. Proposals, spending and pending changes start empty.
+fn init_wallet_config(
+    wallet_config: &mut WalletConfig,
+    authority: Pubkey,
+    bump: u8,
+    snapshot: WalletSnapshot,
+) -> Result<()> {
+    let WalletSnapshot {
+        version,
+        signers,
+        threshold,
+        admin_threshold,
+        emergency_threshold,
+        proposal_timeout,
+        spending_limit,
+        spending_period,
+        allowed_programs,
+        quorum,
+        voting_mode,
+        weight_threshold,
+        threshold_bps,
+        policy,
+        guardian,
+        members,
+        budgets,
+    } = snapshot;
+    require!(version == SNAPSHOT_VERSION, MultisigError::UnsupportedSnapshotVersion);
+    require!(!signers.is_empty(), MultisigError::NoSigners);
+    require!(signers.len() <= MAX_SIGNERS, MultisigError::TooManySigners);
+    require!(threshold > 0, MultisigError::ThresholdIsZero);
+    require!(signers.len() >= threshold as usize, MultisigError::ThresholdExceedsSigners);
+    require!(proposal_timeout > 0, MultisigError::InvalidTimeout);
+    require!(spending_limit > 0, MultisigError::InvalidSpendingLimit);
+    require!(!has_duplicates(&signers), MultisigError::DuplicateSigner);
+    require!(
+        allowed_programs.len() <= MAX_ALLOWED_PROGRAMS,
+        MultisigError::TooManyAllowedPrograms
+    );
+
+    let max_threshold = u8::try_from(signers.len()).unwrap_or(u8::MAX);
+    require!(admin_threshold <= max_threshold, MultisigError::UnachievableAdminThreshold);
+    require!(
+        emergency_threshold > 0 && emergency_threshold <= max_threshold,
+        MultisigError::InvalidThreshold
+    );
+    require!(quorum <= max_threshold, MultisigError::InvalidQuorum);
+
+    require!(members.len() == signers.len(), MultisigError::InvalidSnapshot);
+    require!(budgets.len() <= MAX_BUDGETS, MultisigError::TooManyBudgets);
+    require!(
+        budgets.iter().enumerate().all(|(i, budget)| {
+            budget.limit > 0 && budgets[i + 1..].iter().all(|other| other.tag != budget.tag)
+        }),
+        MultisigError::InvalidSnapshot
+    );
+    validate_policy(&policy, signers.len())?;
+    let total_weight = members.iter().map(|member| member.weight).fold(0, u64::saturating_add);
+    validate_voting_mode(&voting_mode, weight_threshold, threshold_bps, total_weight)?;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    wallet_config.authority = authority;
+    wallet_config.version = WALLET_CONFIG_VERSION;
+    wallet_config.creator = authority;
+    wallet_config.threshold = threshold;
+    wallet_config.admin_threshold = admin_threshold;
+    wallet_config.emergency_threshold = emergency_threshold;
+    wallet_config.proposal_timeout = proposal_timeout;
+    wallet_config.spending_limit = spending_limit;
+    wallet_config.spending_period = spending_period;
+    wallet_config.spending_used = 0;
+    wallet_config.last_spending_reset = current_time;
+    wallet_config.is_active = true;
+    wallet_config.proposal_count = 0;
+    wallet_config.bump = bump;
+    wallet_config.pending_signer_change = None;
+    wallet_config.emergency_logs = Vec::new();
+    wallet_config.voting_mode = voting_mode;
+    wallet_config.weight_threshold = weight_threshold;
+    wallet_config.policy = policy;
+    wallet_config.pending_recovery = None;
+    wallet_config.allowed_programs = allowed_programs;
+    wallet_config.pending_authority = None;
+    wallet_config.quorum = quorum;
+    wallet_config.guardian = guardian;
+    wallet_config.active_proposal_count = 0;
+    wallet_config.terminal_proposal_count = 0;
+    wallet_config.threshold_bps = threshold_bps;
+    wallet_config.signer_set_version = 0;
+    wallet_config.budgets = budgets
+        .into_iter()
+        .map(|budget| Budget {
+            tag: budget.tag,
+            limit: budget.limit,
+            used: 0,
+            last_reset: current_time,
+        })
+        .collect();
+    wallet_config.last_execution_at = 0;
+    wallet_config.pending_spending_limit = None;
+    wallet_config.executing = false;
+    wallet_config.pending_signer = None;
+    wallet_config.config_changes = Vec::new();
+    wallet_config.awaited_dependencies = [0; MAX_AWAITED_DEPENDENCIES];
+    wallet_config.members = signers
+        .iter()
+        .zip(members)
+        .map(|(address, settings)| Member {
+            address: *address,
+            role: settings.role,
+            delegate: None,
+            is_active: true,
+            weight: settings.weight,
+            approvals_made: 0,
+            last_proposal_at: 0,
+        })
+        .collect();
+    wallet_config.signers = signers;
+    wallet_config.refresh_authority_is_signer();
+    require!(wallet_config.thresholds_reachable(), MultisigError::InvalidThreshold);
+    Ok(())
+}
+
+/// Check a proposal description is non-empty and fits its allocated space
+fn validate_description(description: &str) -> Result<()> {
+    require!(!description.is_empty(), MultisigError::EmptyDescription);
+    require!(description.len() <= MAX_DESCRIPTION_LEN, MultisigError::DescriptionTooLong);
+    Ok(())
+}
+
+/// Check the instruction count, merge duplicate account metas in each
+/// instruction, check each instruction's data length and check every
+/// instruction targets a program on the wallet's allowlist, if it has one
+fn validate_instructions(
+    wallet_config: &WalletConfig,
+    instructions: &mut [InstructionData],
+) -> Result<()> {
+    require!(instructions.len() <= MAX_INSTRUCTIONS, MultisigError::TooManyInstructions);
+    for (index, instruction) in instructions.iter_mut().enumerate() {
+        instruction.merge_duplicate_accounts();
+        require!(
+            instruction.accounts.len() <= MAX_INSTRUCTION_ACCOUNTS,
+            MultisigError::TooManyInstructionAccounts
+        );
+        if instruction.data.len() > MAX_INSTRUCTION_DATA_LEN {
+            msg!(
+                "Instruction {} has {} bytes of data; the maximum is {}",
+                index, instruction.data.len(), MAX_INSTRUCTION_DATA_LEN
+            );
+            return err!(MultisigError::InstructionDataTooLarge);
+        }
+
+        let allowed = wallet_config.allowed_programs.is_empty()
+            || wallet_config.allowed_programs.contains(&instruction.program_id);
+        if !allowed {
+            msg!("Program {} is not on the wallet's allowlist", instruction.program_id);
+            return err!(MultisigError::ProgramNotAllowed);
+        }
+    }
+    Ok(())
+}
+
+/// Check a multi-option proposal's options split its instructions exactly.
+/// Options are only allowed on categories without a built-in action, which
+/// would run whichever option wins.
+fn validate_options(
+    category: &ProposalCategory,
+    options: &[ProposalOption],
+    instruction_count: usize,
+) -> Result<()> {
+    if options.is_empty() {
+        return Ok(());
+    }
+    require!(options.len() <= MAX_PROPOSAL_OPTIONS, MultisigError::InvalidProposalOptions);
+    require!(
+        matches!(
+            category,
+            ProposalCategory::Regular | ProposalCategory::Admin | ProposalCategory::Emergency
+        ),
+        MultisigError::InvalidProposalOptions
+    );
+    let option_instructions: usize = options
+        .iter()
+        .map(|option| usize::from(option.instruction_count))
+        .sum();
+    require!(option_instructions == instruction_count, MultisigError::InvalidProposalOptions);
+    Ok(())
+}
+
+/// Check a proposal's required approvers are distinct current signers
+fn validate_required_approvers(wallet_config: &WalletConfig, required_approvers: &[Pubkey]) -> Result<()> {
+    for (index, approver) in required_approvers.iter().enumerate() {
+        require!(
+            wallet_config.signers.contains(approver) && !required_approvers[..index].contains(approver),
+            MultisigError::InvalidRequiredApprovers
+        );
+    }
+    Ok(())
+}
+
+/// Check `proposal` still fits in the space allocated for proposal accounts,
+/// so it fails here rather than when the account is written back
+fn validate_proposal_size(proposal: &Proposal) -> Result<()> {
+    let size = proposal.try_to_vec()?.len();
+    require!(size <= Proposal::INIT_SPACE, MultisigError::ProposalTooLarge);
+    Ok(())
+}
+
+/// Record `approver`'s vote on a pending, unexpired proposal and mark it
+/// approved once the threshold is reached
+fn record_approval(
+    wallet_config: &mut Account<WalletConfig>,
+    proposal: &mut Account<Proposal>,
+    approver: &Pubkey,
+    reason: [u8; 32],
+    option: u8,
+    current_time: i64,
+) -> Result<()> {
+    require!(option < proposal.option_count(), MultisigError::InvalidProposalOption);
+
+    // A delegate votes on behalf of the member who delegated to them
+    let (voter, delegate) = wallet_config.resolve_voter(approver, &proposal.approvers())?;
+    require!(wallet_config.is_active_member(&voter), MultisigError::MemberInactive);
+
+    // Check if already approved
+    if proposal.has_approved(&voter) {
+        let delegated = delegate.is_some()
+            || wallet_config.member(&voter).is_some_and(|member| member.delegate.is_some());
+        if delegated {
+            return err!(MultisigError::DelegatedApprovalConflict);
+        }
+        return err!(MultisigError::AlreadyApproved);
+    }
+    require!(!proposal.has_voted(&voter), MultisigError::AlreadyVoted);
+
+    proposal.approvals.push(Approval {
+        signer: voter,
+        approved_at: current_time,
+        reason,
+        option,
+    });
+    if let Some(member) = wallet_config.members.iter_mut().find(|member| member.address == voter) {
+        member.approvals_made = member.approvals_made.saturating_add(1);
+    }
+
+    emit!(ProposalApproved {
+        wallet: wallet_config.key(),
+        proposal_id: proposal.id,
+        approver: voter,
+        delegate,
+        reason,
+        option,
+        timestamp: current_time,
+    });
+    
+    // Check if threshold is met
+    let approvals_needed = wallet_config.approvals_needed(proposal);
+
+    if approvals_needed == 0
+        && wallet_config.quorum_reached(proposal)
+        && wallet_config.required_approvers_met(proposal)
+    {
+        let option = wallet_config.leading_option(proposal);
+        proposal.mark_approved(option, current_time);
+        msg!("Proposal {} approved with {} votes", proposal.key(), proposal.approvals.len());
+    } else if approvals_needed == 0 && !wallet_config.required_approvers_met(proposal) {
+        msg!("Proposal {} has enough approvals but is missing a required approver", proposal.key());
+    } else if approvals_needed == 0 {
+        msg!("Proposal {} has enough approvals but has not reached quorum", proposal.key());
+    } else {
+        msg!("Proposal {} approved by {}. {} more votes needed", 
+             proposal.key(), voter, approvals_needed);
+    }
+
+    Ok(())
+}
+
+/// Drop approvals from signers no longer in the wallet from the open
+/// proposals in `remaining_accounts`, returning approved proposals that no
+/// longer meet their threshold to pending. Proposals whose execution has
+/// started are left alone. Does nothing unless the policy invalidates
+/// removed signers' approvals.
+fn invalidate_removed_approvals<'info>(
+    wallet_config: &Account<'info, WalletConfig>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    if !wallet_config.policy.invalidate_removed_approvals {
+        return Ok(());
+    }
+
+    for info in remaining_accounts {
+        let mut proposal = Account::<Proposal>::try_from(info)?;
+        require!(proposal.wallet == wallet_config.key(), MultisigError::WalletMismatch);
+        require!(is_current_proposal(&proposal), MultisigError::UnsupportedAccountVersion);
+        if !matches!(proposal.status, ProposalStatus::Pending | ProposalStatus::Approved)
+            || proposal.executed_instruction_count > 0
+        {
+            continue;
+        }
+
+        let approval_count = proposal.approvals.len();
+        proposal
+            .approvals
+            .retain(|approval| wallet_config.signers.contains(&approval.signer));
+        if proposal.approvals.len() == approval_count {
+            continue;
+        }
+
+        let still_approved = wallet_config.approvals_needed(&proposal) == 0
+            && wallet_config.quorum_reached(&proposal)
+            && wallet_config.required_approvers_met(&proposal);
+        if proposal.status == ProposalStatus::Approved && !still_approved {
+            proposal.set_status(ProposalStatus::Pending);
+            proposal.approved_at = None;
+            proposal.selected_option = None;
+            msg!("Proposal {} returned to pending", proposal.key());
+        }
+        proposal.exit(&crate::ID)?;
+    }
+    Ok(())
+}
+
+/// The layout version of a wallet config account, or `None` if this program
+/// can't read it. That covers accounts from newer program versions and those
+/// written before wallet configs were versioned, whose byte at
+/// `WALLET_CONFIG_VERSION_OFFSET` belongs to the signer list's length; a
+/// version only counts when the account also has that version's size.
+fn wallet_config_version(info: &AccountInfo) -> Option<u8> {
+    let data = info.try_borrow_data().ok()?;
+    if !data.starts_with(WalletConfig::DISCRIMINATOR) {
+        return None;
+    }
+    let version = *data.get(WALLET_CONFIG_VERSION_OFFSET)?;
+    let space = WALLET_CONFIG_SPACES.get(usize::from(version).checked_sub(1)?)?;
+    (data.len() == *space).then_some(version)
+}
+
+/// The authority of a wallet config account. It precedes the version, so it
+/// sits at the same offset in every layout.
+fn wallet_config_authority(info: &AccountInfo) -> Option<Pubkey> {
+    let data = info.try_borrow_data().ok()?;
+    let authority = data.get(8..WALLET_CONFIG_VERSION_OFFSET)?;
+    Pubkey::try_from(authority).ok()
+}
+
+/// Whether `wallet_config` has the current layout
+fn is_current_wallet_config(wallet_config: &Account<WalletConfig>) -> bool {
+    wallet_config_version(&wallet_config.to_account_info()) == Some(WALLET_CONFIG_VERSION)
+}
+
+/// Whether `proposal` has the current layout. Proposals written before
+/// versioning are smaller, and their byte at `PROPOSAL_VERSION_OFFSET`
+/// belongs to the description's length.
+fn is_current_proposal(proposal: &Account<Proposal>) -> bool {
+    proposal.version == PROPOSAL_VERSION && proposal.to_account_info().data_len() == 8 + Proposal::INIT_SPACE
+}
+
+/// Whether the wallet's proposal `proposal_id`, passed through
+/// `remaining_accounts`, has been executed. A closed proposal no longer
+/// records its outcome, so it never counts as executed.
+fn proposal_executed<'info>(
+    wallet_info: &AccountInfo<'info>,
+    proposal_id: u64,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<bool> {
+    let (address, _) = Pubkey::find_program_address(
+        &[b"proposal", wallet_info.key.as_ref(), &proposal_id.to_le_bytes()],
+        &crate::ID,
+    );
+    let info = find_account_info(&address, wallet_info, remaining_accounts)?;
+    if info.owner != &crate::ID {
+        return Ok(false);
+    }
+    let data = info.try_borrow_data()?;
+    Ok(Proposal::try_deserialize(&mut &data[..])
+        .is_ok_and(|proposal| proposal.status == ProposalStatus::Executed))
+}
+
+/// Move `lamports` the wallet PDA holds above its rent-exempt minimum to
+/// `to`. The PDA carries data, so the System program can't debit it; as its
+/// owner, this program moves the lamports directly.
+fn transfer_wallet_lamports(wallet_info: &AccountInfo, to: &AccountInfo, lamports: u64) -> Result<()> {
+    let balance = wallet_info.lamports();
+    let available = balance.saturating_sub(Rent::get()?.minimum_balance(wallet_info.data_len()));
+    require!(lamports <= available, MultisigError::InsufficientWalletBalance);
+    **wallet_info.try_borrow_mut_lamports()? = balance - lamports;
+    let received = to.lamports().checked_add(lamports).ok_or(MultisigError::ArithmeticOverflow)?;
+    **to.try_borrow_mut_lamports()? = received;
+    Ok(())
+}
+
+/// Find the account info for `key` among the wallet PDA and `remaining_accounts`
+fn find_account_info<'info>(
+    key: &Pubkey,
+    wallet_info: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<AccountInfo<'info>> {
+    if key == wallet_info.key {
+        return Ok(wallet_info.clone());
+    }
+    remaining_accounts
+        .iter()
+        .find(|info| info.key == key)
+        .cloned()
+        .ok_or_else(|| error!(MultisigError::MissingInstructionAccount))
+}
+
+/// Deserialize a token account or mint owned by the SPL Token program
+fn load_token_state<T: AccountDeserialize>(info: &AccountInfo) -> Result<T> {
+    require_keys_eq!(*info.owner, token::ID, ErrorCode::AccountOwnedByWrongProgram);
+    T::try_deserialize(&mut &info.try_borrow_data()?[..])
+}
+
+#[derive(Accounts)]
+pub struct InitializeWallet<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + WalletConfig::INIT_SPACE,
+        seeds = [b"wallet_config", authority.key().as_ref()],
+        bump
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExportConfig<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct AddProposal<'info> {
+    #[account(
+        init,
+        payer = fee_payer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", wallet_config.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub proposer: Signer<'info>,
+    
+    /// Pays the proposal's rent; may be the proposer or a sponsor
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.status == ProposalStatus::Pending,
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReconfirmApproval<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposalsBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RejectProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.status == ProposalStatus::Pending,
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub rejector: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AbstainProposal<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = proposal.status == ProposalStatus::Pending,
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub voter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.status == ProposalStatus::Approved,
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    /// Receives the policy's execution bounty, if any
+    #[account(mut)]
+    pub executor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RecordExecutionFailure<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub reporter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct IsExecutable<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewSpending<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct GetProposalSummary<'info> {
+    #[account(constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct TimeUntilExpiry<'info> {
+    #[account(constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct CompactApprovals<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct PurgeExpired<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+}
+
+#[derive(Accounts)]
+pub struct EditProposal<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VetoProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.wallet == wallet_config.key(),
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    #[account(
+        mut,
+        constraint = proposal.wallet == wallet_config.key(),
+        close = fee_payer,
+        constraint = is_current_proposal(&proposal) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    /// CHECK: receives the rent refund; must be whoever paid the rent
+    #[account(mut, address = proposal.fee_payer)]
+    pub fee_payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSigners<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSpendingLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptSignerRole<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub nominee: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetVotingMode<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetSpendingWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SpendingRemaining<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMemberWeight<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMemberActive<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub delegator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
     )]
     pub wallet_config: Account<'info, WalletConfig>,
     
+    pub delegator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetMemberStatus<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CheckInvariants<'info> {
+    #[account(
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+}
+
+// The wallet config may predate the current layout, so it is reallocated by
+// hand rather than with Anchor's `realloc` constraint. That constraint only
+// applies to `Account` fields, which deserialize the old layout as the current
+// one first, failing when the bytes for appended fields are missing or stale.
+#[derive(Accounts)]
+pub struct MigrateWalletConfig<'info> {
+    /// CHECK: `migrate_wallet_config` deserializes it once it has the current
+    /// size; until then only its version and authority are read
+    #[account(
+        mut,
+        owner = crate::ID,
+        constraint = wallet_config_version(&wallet_config).is_some() @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config_authority(&wallet_config) == Some(authority.key()) @ MultisigError::NotAuthorized
+    )]
+    pub wallet_config: UncheckedAccount<'info>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
-#[derive(Accounts)]
-pub struct AddProposal<'info> {
-    #[account(
-        init,
-        payer = proposer,
-        space = 8 + Proposal::INIT_SPACE,
-        seeds = [b"proposal", wallet_config.key().as_ref(), proposer.key().as_ref()],
-        bump
-    )]
-    pub proposal: Account<'info, Proposal>,
-    
-    #[account(
-        mut,
-        seeds = [b"wallet_config", wallet_config.authority.as_ref()],
-        bump = wallet_config.bump,
-        constraint = wallet_config.is_active
-    )]
-    pub wallet_config: Account<'info, WalletConfig>,
-    
-    #[account(mut)]
-    pub proposer: Signer<'info>,
-    pub system_program: Program<'info, System>,
+// Pausing and resuming must work regardless of `is_active`
+#[derive(Accounts)]
+pub struct PauseWallet<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResumeWallet<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyOverride<'info> {
+    #[account(
+        mut,
+        seeds = [b"wallet_config", wallet_config.creator.as_ref()],
+        bump = wallet_config.bump,
+        constraint = is_current_wallet_config(&wallet_config) @ MultisigError::UnsupportedAccountVersion,
+        constraint = wallet_config.is_active,
+        constraint = !wallet_config.executing @ MultisigError::ReentrantCall
+    )]
+    pub wallet_config: Account<'info, WalletConfig>,
+    
+    pub emergency_authority: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct WalletConfig {
+    pub authority: Pubkey,
+    pub version: u8, // `WALLET_CONFIG_VERSION` at write, at `WALLET_CONFIG_VERSION_OFFSET`; see `wallet_config_version`
+    #[max_len(MAX_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub admin_threshold: u8,
+    pub emergency_threshold: u8,
+    pub proposal_timeout: i64,
+    pub spending_limit: u64,
+    pub spending_period: i64,
+    pub spending_used: u64,
+    pub last_spending_reset: i64,
+    pub is_active: bool,
+    #[max_len(MAX_SIGNERS)]
+    pub members: Vec<Member>,
+    pub proposal_count: u64, // Proposal PDA nonce; only ever increases
+    pub bump: u8,
+    pub pending_signer_change: Option<SignerChange>,
+    #[max_len(EMERGENCY_LOG_CAPACITY)] // Most recent emergency overrides
+    pub emergency_logs: Vec<EmergencyLog>,
+    pub voting_mode: VotingMode,
+    pub weight_threshold: u64,
+    pub policy: WalletPolicy,
+    pub pending_recovery: Option<Recovery>,
+    #[max_len(MAX_ALLOWED_PROGRAMS)] // Empty allows any program
+    pub allowed_programs: Vec<Pubkey>,
+    pub creator: Pubkey, // Original authority; seeds the wallet PDA
+    pub pending_authority: Option<Pubkey>,
+    pub quorum: u8, // Minimum votes, including abstentions, for approval
+    pub guardian: Option<Pubkey>, // May veto proposals before execution
+    pub active_proposal_count: u64, // Pending or approved proposals
+    pub terminal_proposal_count: u64, // Finished proposals not yet closed
+    pub threshold_bps: u16, // Share of signers needed in proportional mode
+    pub signer_set_version: u64, // Bumped whenever the signer set changes
+    #[max_len(MAX_BUDGETS)]
+    pub budgets: Vec<Budget>, // Per-tag spending budgets within the overall limit
+    pub last_execution_at: i64, // When the most recent proposal execution started
+    pub authority_is_signer: bool, // Whether the authority is also one of the signers
+    pub pending_spending_limit: Option<PendingSpendingLimit>,
+    pub executing: bool, // Set while a proposal's instructions are being invoked
+    pub pending_signer: Option<PendingSigner>,
+    #[max_len(CONFIG_LOG_CAPACITY)] // Most recent spending limit and signer changes
+    pub config_changes: Vec<ConfigChange>,
+    // Added in version 2: ids, plus one, of proposals that open proposals
+    // depend on and so can't be closed yet; zero marks a free slot
+    pub awaited_dependencies: [u64; MAX_AWAITED_DEPENDENCIES],
+}
+
+impl WalletConfig {
+    /// Number of approvals a proposal of `category` needs
+    pub fn required_threshold(&self, category: &ProposalCategory) -> u8 {
+        match category {
+            ProposalCategory::Regular
+            | ProposalCategory::TokenTransfer { .. }
+            | ProposalCategory::SolTransfer { .. } => self.threshold,
+            ProposalCategory::Admin | ProposalCategory::SpendingLimitChange { .. } => self.admin_threshold,
+            ProposalCategory::Emergency => self.emergency_threshold,
+            ProposalCategory::SignerChange { .. } => u8::try_from(self.voting_signer_count()).unwrap_or(u8::MAX),
+        }
+    }
+
+    /// Highest number of approvals any proposal category needs
+    pub fn max_threshold(&self) -> u8 {
+        self.threshold
+            .max(self.admin_threshold)
+            .max(self.emergency_threshold)
+    }
+
+    /// Signers whose votes count toward thresholds: every active signer, less
+    /// the authority when the policy excludes its votes
+    pub fn voting_signer_count(&self) -> usize {
+        self.signers
+            .iter()
+            .filter(|signer| self.is_active_member(signer))
+            .filter(|signer| !(self.policy.exclude_authority_votes && **signer == self.authority))
+            .count()
+    }
+
+    /// Whether `address` has not been deactivated; addresses without a
+    /// member entry count as active
+    pub fn is_active_member(&self, address: &Pubkey) -> bool {
+        self.member(address).is_none_or(|member| member.is_active)
+    }
+
+    /// Number of signers whose member entry is active
+    pub fn active_signer_count(&self) -> usize {
+        self.members
+            .iter()
+            .filter(|member| member.is_active && self.signers.contains(&member.address))
+            .count()
+    }
+
+    /// Whether the voting signers can meet every category threshold
+    pub fn thresholds_reachable(&self) -> bool {
+        self.max_threshold() as usize <= self.voting_signer_count()
+    }
+
+    /// Bitmask of the `INVARIANT_*` flags this wallet violates at
+    /// `current_time`
+    pub fn invariant_violations(&self, current_time: i64) -> u8 {
+        let mut violations = 0;
+
+        let excluded = self.policy.exclude_authority_votes && self.authority_is_signer;
+        let active_voters = self
+            .signers
+            .iter()
+            .filter(|signer| self.is_active_member(signer) && !(excluded && **signer == self.authority))
+            .count();
+        if self.threshold == 0 || self.max_threshold() as usize > active_voters {
+            violations |= INVARIANT_THRESHOLD;
+        }
+
+        let members_match = self.members.len() == self.signers.len()
+            && !has_duplicates(&self.signers)
+            && self.signers.iter().all(|signer| self.member(signer).is_some());
+        if !members_match {
+            violations |= INVARIANT_MEMBERS;
+        }
+
+        if self.spending_period <= 0 || self.last_spending_reset > current_time {
+            violations |= INVARIANT_SPENDING;
+        }
+
+        let tracked = self.active_proposal_count.checked_add(self.terminal_proposal_count);
+        if tracked.is_none_or(|tracked| tracked > self.proposal_count) {
+            violations |= INVARIANT_PROPOSAL_COUNTS;
+        }
+
+        violations
+    }
+
+    /// Whether `key` may manage membership: the authority or an Admin member
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.authority == *key
+            || self.member(key).is_some_and(|member| member.role == MemberRole::Admin)
+    }
+
+    /// Whether `key` may manage the spending window: the authority or a
+    /// Treasurer member
+    pub fn is_treasurer(&self, key: &Pubkey) -> bool {
+        self.authority == *key
+            || self.member(key).is_some_and(|member| member.role == MemberRole::Treasurer)
+    }
+
+    /// Look up the member entry for `address`
+    pub fn member(&self, address: &Pubkey) -> Option<&Member> {
+        self.members.iter().find(|member| member.address == *address)
+    }
+
+    /// Voting weight of `address`; signers without a member entry weigh one
+    pub fn vote_weight(&self, address: &Pubkey) -> u64 {
+        self.member(address).map_or(1, |member| member.weight)
+    }
+
+    /// Settings needed to recreate this wallet; see `export_config`
+    pub fn snapshot(&self) -> WalletSnapshot {
+        WalletSnapshot {
+            version: SNAPSHOT_VERSION,
+            signers: self.signers.clone(),
+            threshold: self.threshold,
+            admin_threshold: self.admin_threshold,
+            emergency_threshold: self.emergency_threshold,
+            proposal_timeout: self.proposal_timeout,
+            spending_limit: self.spending_limit,
+            spending_period: self.spending_period,
+            allowed_programs: self.allowed_programs.clone(),
+            quorum: self.quorum,
+            voting_mode: self.voting_mode.clone(),
+            weight_threshold: self.weight_threshold,
+            threshold_bps: self.threshold_bps,
+            policy: self.policy.clone(),
+            guardian: self.guardian,
+            members: self
+                .signers
+                .iter()
+                .map(|signer| MemberSettings {
+                    role: self.member(signer).map_or(MemberRole::Member, |member| member.role.clone()),
+                    weight: self.vote_weight(signer),
+                })
+                .collect(),
+            budgets: self
+                .budgets
+                .iter()
+                .map(|budget| BudgetLimit {
+                    tag: budget.tag,
+                    limit: budget.limit,
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether `address` is a signer, its membership details and who casts
+    /// its vote: its delegate if it has one, otherwise itself. Only signers
+    /// have an effective voter.
+    pub fn member_status(&self, address: &Pubkey) -> MemberStatus {
+        let is_signer = self.signers.contains(address);
+        let member = self.member(address);
+        let delegate = member.and_then(|member| member.delegate);
+        MemberStatus {
+            is_signer,
+            role: member.map(|member| member.role.clone()),
+            is_active: member.is_some_and(|member| member.is_active),
+            delegate,
+            effective_voter: is_signer.then(|| delegate.unwrap_or(*address)),
+        }
+    }
+
+    /// Combined voting weight of `voters`
+    pub fn total_weight(&self, voters: &[Pubkey]) -> u64 {
+        voters
+            .iter()
+            .map(|voter| self.vote_weight(voter))
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Votes (or weight, in weighted mode) `proposal` still needs on top of
+    /// its approvals; zero once it is approved
+    pub fn approvals_needed(&self, proposal: &Proposal) -> u64 {
+        let approvers = proposal.option_approvers(self.leading_option(proposal));
+        self.votes_short(&proposal.category, &self.counted_votes(proposal, &approvers))
+    }
+
+    /// Like `approvals_needed`, but only counting approvals still within the
+    /// policy's approval validity window at `current_time`
+    pub fn fresh_approvals_needed(&self, proposal: &Proposal, current_time: i64) -> u64 {
+        let fresh = self.current_approvers(
+            proposal,
+            proposal.fresh_approvers(self.leading_option(proposal), self.policy.approval_validity, current_time),
+        );
+        self.votes_short(&proposal.category, &self.counted_votes(proposal, &fresh))
+    }
+
+    /// Like `approvals_needed`, but ignoring approvals from signers removed
+    /// since `proposal` was created when the policy invalidates them
+    pub fn current_approvals_needed(&self, proposal: &Proposal) -> u64 {
+        let current = self.current_approvers(proposal, proposal.option_approvers(self.leading_option(proposal)));
+        self.votes_short(&proposal.category, &self.counted_votes(proposal, &current))
+    }
+
+    /// Whether every required approver of `proposal` has approved its
+    /// leading option
+    pub fn required_approvers_met(&self, proposal: &Proposal) -> bool {
+        proposal.has_required_approvers(&proposal.option_approvers(self.leading_option(proposal)))
+    }
+
+    /// Like `required_approvers_met`, but only counting approvals still
+    /// within the policy's approval validity window at `current_time`
+    pub fn fresh_required_approvers_met(&self, proposal: &Proposal, current_time: i64) -> bool {
+        let fresh = proposal.fresh_approvers(
+            self.leading_option(proposal),
+            self.policy.approval_validity,
+            current_time,
+        );
+        proposal.has_required_approvers(&fresh)
+    }
+
+    /// The option whose approvals count toward `proposal`'s threshold: the
+    /// winning option once one is selected, otherwise the one closest to
+    /// the threshold (the lowest index on ties). Single-option proposals only
+    /// have option 0.
+    pub fn leading_option(&self, proposal: &Proposal) -> u8 {
+        if let Some(option) = proposal.selected_option {
+            return option;
+        }
+        (0..proposal.option_count())
+            .min_by_key(|option| {
+                let approvers = proposal.option_approvers(*option);
+                self.votes_short(&proposal.category, &self.counted_votes(proposal, &approvers))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Drop `approvers` no longer in the signer set if it has changed since
+    /// `proposal` was created and the policy invalidates removed signers'
+    /// approvals
+    fn current_approvers(&self, proposal: &Proposal, approvers: Vec<Pubkey>) -> Vec<Pubkey> {
+        if !self.policy.invalidate_removed_approvals
+            || proposal.signer_set_version == self.signer_set_version
+        {
+            return approvers;
+        }
+        approvers
+            .into_iter()
+            .filter(|approver| self.signers.contains(approver))
+            .collect()
+    }
+
+    /// Whether enough active members have voted on `proposal`, either way or
+    /// by abstaining, to meet the wallet's quorum
+    pub fn quorum_reached(&self, proposal: &Proposal) -> bool {
+        let approvers = proposal.approvals.iter().map(|approval| &approval.signer);
+        let rejectors = proposal.rejections.iter().map(|rejection| &rejection.signer);
+        let voters = approvers.chain(rejectors).chain(&proposal.abstentions);
+        voters.filter(|voter| self.is_active_member(voter)).count() >= self.quorum as usize
+    }
+
+    /// Whether the active signers who have not rejected `proposal` could
+    /// still approve it
+    pub fn can_still_approve(&self, proposal: &Proposal) -> bool {
+        let remaining: Vec<Pubkey> = self
+            .signers
+            .iter()
+            .filter(|signer| self.is_active_member(signer) && !proposal.has_rejected(signer))
+            .copied()
+            .collect();
+        self.votes_short(&proposal.category, &self.counted_votes(proposal, &remaining)) == 0
+    }
+
+    /// Whether `proposal` has as many rejections as the policy's rejection
+    /// threshold, if one is set
+    pub fn rejection_threshold_reached(&self, proposal: &Proposal) -> bool {
+        self.policy.rejection_threshold > 0
+            && proposal.rejections.len() >= self.policy.rejection_threshold as usize
+    }
+
+    /// Votes a proposal of `category` needs beyond those cast by `voters`.
+    /// Signer changes are counted by signer even in weighted mode, so they
+    /// always need every signer.
+    fn votes_short(&self, category: &ProposalCategory, voters: &[Pubkey]) -> u64 {
+        match (&self.voting_mode, category) {
+            (VotingMode::Count, _) | (_, ProposalCategory::SignerChange { .. }) => {
+                u64::from(self.required_threshold(category)).saturating_sub(voters.len() as u64)
+            }
+            (VotingMode::Weighted, _) => self.weight_threshold.saturating_sub(self.total_weight(voters)),
+            (VotingMode::Proportional, _) => {
+                self.proportional_threshold().saturating_sub(voters.len() as u64)
+            }
+        }
+    }
+
+    /// Approvals needed in proportional mode: `threshold_bps` of the current
+    /// signers, rounded up
+    pub fn proportional_threshold(&self) -> u64 {
+        (self.signers.len() as u64)
+            .saturating_mul(u64::from(self.threshold_bps))
+            .div_ceil(u64::from(BPS_DENOMINATOR))
+    }
+
+    /// The `voters` whose approval counts toward `proposal`'s threshold
+    fn counted_votes(&self, proposal: &Proposal, voters: &[Pubkey]) -> Vec<Pubkey> {
+        let exclude_proposer = self.policy.independent_emergency_approval
+            && proposal.category == ProposalCategory::Emergency;
+        voters
+            .iter()
+            .filter(|voter| !(exclude_proposer && **voter == proposal.proposer))
+            .filter(|voter| !(self.policy.exclude_authority_votes && **voter == self.authority))
+            .filter(|voter| self.is_active_member(voter))
+            .copied()
+            .collect()
+    }
+
+    /// Work out whose vote an approval from `approver` counts as. Signers
+    /// vote for themselves; a delegate votes for the first member delegating
+    /// to them that has not yet voted. Returns the voter and, when voting by
+    /// delegation, the delegate.
+    pub fn resolve_voter(
+        &self,
+        approver: &Pubkey,
+        approvals: &[Pubkey],
+    ) -> Result<(Pubkey, Option<Pubkey>)> {
+        if self.signers.contains(approver) {
+            return Ok((*approver, None));
+        }
+        if self.pending_signer.as_ref().is_some_and(|pending| pending.address == *approver) {
+            return err!(MultisigError::PendingSignerNotAccepted);
+        }
+
+        let mut delegators = self
+            .members
+            .iter()
+            .filter(|member| {
+                member.delegate == Some(*approver) && self.signers.contains(&member.address)
+            })
+            .peekable();
+        require!(delegators.peek().is_some(), MultisigError::NotAuthorized);
+
+        delegators
+            .find(|member| !approvals.contains(&member.address))
+            .map(|member| (member.address, Some(*approver)))
+            .ok_or_else(|| error!(MultisigError::DelegatedApprovalConflict))
+    }
+
+    /// Check `address` is not yet a member or signer and there is room for
+    /// another
+    pub fn check_can_add_signer(&self, address: &Pubkey) -> Result<()> {
+        require!(
+            self.member(address).is_none() && !self.signers.contains(address),
+            MultisigError::MemberAlreadyExists
+        );
+        require!(
+            self.members.len() < MAX_SIGNERS && self.signers.len() < MAX_SIGNERS,
+            MultisigError::MemberLimitReached
+        );
+        Ok(())
+    }
+
+    /// Add `address` as a member with `role` and as a signer
+    pub fn add_signer(&mut self, address: Pubkey, role: MemberRole) {
+        self.members.push(Member {
+            address,
+            role,
+            delegate: None,
+            is_active: true,
+            weight: 1,
+            approvals_made: 0,
+            last_proposal_at: 0,
+        });
+        self.signers.push(address);
+
+        // Approvals gathered for the old signer set no longer apply
+        self.pending_signer_change = None;
+        self.record_signer_change();
+    }
+
+    /// Replace the signer set and threshold, keeping the member entries
+    /// (roles, delegates and weights) of retained signers, adding new signers
+    /// as plain members and dropping removed ones
+    pub fn replace_signers(&mut self, signers: Vec<Pubkey>, threshold: u8) {
+        let mut members = Vec::with_capacity(signers.len());
+        for signer in &signers {
+            let member = self.member(signer).cloned().unwrap_or(Member {
+                address: *signer,
+                role: MemberRole::Member,
+                delegate: None,
+                is_active: true,
+                weight: 1,
+                approvals_made: 0,
+                last_proposal_at: 0,
+            });
+            members.push(member);
+        }
+
+        // Nobody can keep voting through a removed signer
+        for member in &mut members {
+            if member.delegate.is_some_and(|delegate| {
+                self.signers.contains(&delegate) && !signers.contains(&delegate)
+            }) {
+                member.delegate = None;
+            }
+        }
+
+        self.members = members;
+        self.signers = signers;
+        self.threshold = threshold;
+        // Approvals gathered for the old signer set no longer apply
+        self.pending_signer_change = None;
+        self.record_signer_change();
+    }
+
+    /// Note that the signer set changed, so open proposals recount their
+    /// approvals against it
+    pub fn record_signer_change(&mut self) {
+        self.signer_set_version = self.signer_set_version.wrapping_add(1);
+        self.refresh_authority_is_signer();
+    }
+
+    /// Recompute `authority_is_signer` after the signers or authority change
+    pub fn refresh_authority_is_signer(&mut self) {
+        self.authority_is_signer = self.signers.contains(&self.authority);
+    }
+
+    /// Count a pending or approved proposal as finished
+    pub fn record_proposal_finished(&mut self) {
+        self.active_proposal_count = self.active_proposal_count.saturating_sub(1);
+        self.terminal_proposal_count = self.terminal_proposal_count.saturating_add(1);
+    }
+
+    /// Append to the emergency log, dropping the oldest entry when full
+    pub fn record_emergency(&mut self, entry: EmergencyLog) {
+        if self.emergency_logs.len() >= EMERGENCY_LOG_CAPACITY {
+            self.emergency_logs.remove(0);
+        }
+        self.emergency_logs.push(entry);
+    }
+
+    /// Append to the config change log, dropping the oldest entry when full
+    pub fn record_config_change(&mut self, kind: ConfigChangeKind, actor: Pubkey, timestamp: i64) {
+        if self.config_changes.len() >= CONFIG_LOG_CAPACITY {
+            self.config_changes.remove(0);
+        }
+        self.config_changes.push(ConfigChange { kind, timestamp, actor });
+    }
+
+    /// Keep proposal `dependency_id` from being closed until a proposal that
+    /// depends on it has run or been closed
+    pub fn await_dependency(&mut self, dependency_id: u64) -> Result<()> {
+        let slot = self
+            .awaited_dependencies
+            .iter_mut()
+            .find(|slot| **slot == 0)
+            .ok_or(MultisigError::TooManyAwaitedDependencies)?;
+        *slot = dependency_id.saturating_add(1);
+        Ok(())
+    }
+
+    /// Drop one proposal's wait on proposal `dependency_id`
+    pub fn release_dependency(&mut self, dependency_id: u64) {
+        let awaited = dependency_id.saturating_add(1);
+        if let Some(slot) = self.awaited_dependencies.iter_mut().find(|slot| **slot == awaited) {
+            *slot = 0;
+        }
+    }
+
+    /// Drop `proposal`'s wait on its dependency if it never started
+    /// executing, which is when the dependency is released otherwise
+    pub fn release_unmet_dependency(&mut self, proposal: &Proposal) {
+        let started = proposal.status == ProposalStatus::Executed || proposal.executed_instruction_count > 0;
+        if let Some(dependency_id) = proposal.depends_on.filter(|_| !started) {
+            self.release_dependency(dependency_id);
+        }
+    }
+
+    /// Whether `approver`'s vote on `proposal` respects the policy's order of
+    /// required approvers. Approvers who can't vote at all pass, leaving
+    /// `record_approval` to report why.
+    pub fn approval_in_order(&self, proposal: &Proposal, approver: &Pubkey) -> bool {
+        !self.policy.ordered_required_approvers
+            || self
+                .resolve_voter(approver, &proposal.approvers())
+                .map_or(true, |(voter, _)| proposal.approval_in_order(&voter))
+    }
+
+    /// Whether an open proposal depends on proposal `proposal_id`
+    pub fn is_awaited(&self, proposal_id: u64) -> bool {
+        self.awaited_dependencies.contains(&proposal_id.saturating_add(1))
+    }
+
+    /// Start a new spending window if the current one has elapsed
+    pub fn refresh_spending_window(&mut self, current_time: i64) {
+        if self.spending_window_elapsed(current_time) {
+            self.spending_used = 0;
+            self.last_spending_reset = current_time;
+        }
+    }
+
+    /// Amount already spent in the window that `current_time` falls in
+    pub fn current_spending_used(&self, current_time: i64) -> u64 {
+        if self.spending_window_elapsed(current_time) {
+            0
+        } else {
+            self.spending_used
+        }
+    }
+
+    /// Spending that executing `proposal` at `current_time` would add to the
+    /// current window. A spend that overflows is reported as `u64::MAX`.
+    pub fn preview_spending(&self, proposal: &Proposal, current_time: i64) -> SpendingPreview {
+        let projected = proposal
+            .spend_amount()
+            .and_then(|amount| amount.checked_add(self.policy.execution_bounty))
+            .and_then(|amount| self.current_spending_used(current_time).checked_add(amount));
+        let exceeds_budget = self
+            .budgets
+            .iter()
+            .find(|budget| budget.tag == proposal.tag)
+            .is_some_and(|budget| {
+                proposal
+                    .spend_amount()
+                    .and_then(|amount| budget.current_used(self.spending_period, current_time).checked_add(amount))
+                    .is_none_or(|used| used > budget.limit)
+            });
+        SpendingPreview {
+            amount: proposal.spend_amount().unwrap_or(u64::MAX),
+            projected_spending_used: projected.unwrap_or(u64::MAX),
+            exceeds_limit: exceeds_budget
+                || projected.is_none_or(|spending_used| spending_used > self.spending_limit),
+        }
+    }
+
+    /// Whether the policy's minimum interval since the last proposal
+    /// execution started has passed
+    pub fn execution_cooldown_elapsed(&self, current_time: i64) -> bool {
+        self.last_execution_at == 0
+            || current_time >= self.last_execution_at.saturating_add(self.policy.min_execution_interval)
+    }
+
+    /// Whether `limit` per `period` would let the wallet spend more than its
+    /// current limits: a higher limit or a shorter period
+    pub fn is_spending_increase(&self, limit: u64, period: i64) -> bool {
+        limit > self.spending_limit || period < self.spending_period
+    }
+
+    /// Whether the spending window containing `last_spending_reset` is over
+    pub fn spending_window_elapsed(&self, current_time: i64) -> bool {
+        current_time >= self.last_spending_reset.saturating_add(self.spending_period)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub wallet: Pubkey,
+    pub proposer: Pubkey,
+    pub tag: [u8; 16], // Client-defined label; fixed offset so it can be memcmp filtered
+    pub status_code: u8, // `status.code()`, at `PROPOSAL_STATUS_CODE_OFFSET` for memcmp filtering
+    pub version: u8, // `PROPOSAL_VERSION` at write, at `PROPOSAL_VERSION_OFFSET`
+    #[max_len(MAX_DESCRIPTION_LEN)]
+    pub description: String,
+    pub category: ProposalCategory,
+    #[max_len(MAX_INSTRUCTIONS)]
+    pub instructions: Vec<InstructionData>,
+    pub expiration: i64,
+    pub status: ProposalStatus,
+    #[max_len(MAX_SIGNERS)] // One approval per signer
+    pub approvals: Vec<Approval>,
+    #[max_len(MAX_SIGNERS)] // One rejection per signer
+    pub rejections: Vec<Rejection>,
+    #[max_len(MAX_SIGNERS)] // One abstention per signer
+    pub abstentions: Vec<Pubkey>,
+    pub created_at: i64,
+    pub executed_at: Option<i64>,
+    pub approved_at: Option<i64>,
+    pub executed_by: Option<Pubkey>,
+    pub metadata_hash: [u8; 32], // Hash of an off-chain proposal document; zeroed if none
+    pub executed_instruction_count: u16, // Instructions run so far by chunked execution
+    pub price_condition: Option<PriceCondition>,
+    pub fee_payer: Pubkey, // Paid the rent; refunded when the proposal is closed
+    pub depends_on: Option<u64>, // Id of a proposal that must execute first
+    pub failed_attempts: u32, // Execution failures reported by signers
+    pub last_execution_error: Option<u32>, // Error code of the latest reported failure
+    pub signer_set_version: u64, // Wallet's signer set version when the proposal was created
+    pub not_before: Option<i64>, // Earliest time the proposal may execute
+    #[max_len(MAX_PROPOSAL_OPTIONS)] // Empty for single-option proposals
+    pub options: Vec<ProposalOption>,
+    pub selected_option: Option<u8>, // Winning option once a multi-option proposal is approved
+    #[max_len(MAX_SIGNERS)] // Signers who must approve before the proposal is approved
+    pub required_approvers: Vec<Pubkey>,
+    pub id: u64,
+    pub bump: u8,
+}
+
+/// Record of an emergency override
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct EmergencyLog {
+    pub timestamp: i64,
+    pub authority: Pubkey,
+    pub instruction_count: u8,
+}
+
+/// Kind of configuration change recorded in a wallet's config change log
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ConfigChangeKind {
+    SpendingLimits,
+    Signers,
+    MemberAdded,
+    MemberRemoved,
+}
+
+/// Record of a spending limit or signer change
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct ConfigChange {
+    pub kind: ConfigChangeKind,
+    pub timestamp: i64,
+    pub actor: Pubkey,
+}
+
+/// Approval and execution rules the authority can adjust after setup
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Default)]
+pub struct WalletPolicy {
+    /// Don't count the proposer's own approval toward an Emergency proposal
+    pub independent_emergency_approval: bool,
+    /// Seconds an approved proposal must wait before it can be executed
+    pub execution_delay: i64,
+    /// Signers needed to back a recovery; 0 disables recovery
+    pub recovery_threshold: u8,
+    /// Seconds between initiating and completing a recovery
+    pub recovery_delay: i64,
+    /// Seconds an approval keeps counting toward execution; 0 keeps
+    /// approvals valid until the proposal expires
+    pub approval_validity: i64,
+    /// Seconds a member must wait between submitting proposals
+    pub min_proposal_interval: i64,
+    /// Let proposers edit proposals that already have approvals, clearing
+    /// those approvals
+    pub allow_edits_after_approval: bool,
+    /// Seconds between starting one proposal execution and the next
+    pub min_execution_interval: i64,
+    /// Record the proposer's approval when a signer submits a proposal
+    pub auto_approve_proposer: bool,
+    /// Rejections that reject a proposal outright; 0 only rejects once the
+    /// threshold can no longer be reached
+    pub rejection_threshold: u8,
+    /// Don't count the authority's approvals toward any threshold, even if
+    /// it is a signer
+    pub exclude_authority_votes: bool,
+    /// Seconds a proposed spending limit change waits before it can be
+    /// applied; 0 also allows immediate `set_spending_limits` calls
+    pub spending_limit_delay: i64,
+    /// Seconds past its expiration an approved proposal can still be
+    /// executed; voting still closes at the expiration
+    pub execution_grace_period: i64,
+    /// Pending or approved proposals the wallet may have at once; 0 for no
+    /// cap. Executing, rejecting, cancelling or expiring one frees a slot.
+    pub max_open_proposals: u16,
+    /// Seconds after creation before a proposal can be executed, even if it
+    /// reaches its threshold sooner; unlike `execution_delay` this runs from
+    /// submission rather than approval
+    pub min_voting_period: i64,
+    /// Stop counting a removed signer's approvals: they are dropped from the
+    /// open proposals passed to `remove_member` or `update_signers`,
+    /// returning approved proposals that fall short of their threshold to
+    /// pending, and ignored when executing any other proposal. Otherwise
+    /// they keep counting.
+    pub invalidate_removed_approvals: bool,
+    /// Lamports paid from the wallet PDA to whoever completes a proposal's
+    /// execution, counted against the spending limit; at most
+    /// `MAX_EXECUTION_BOUNTY`
+    pub execution_bounty: u64,
+    /// Only let the authority lower spending limits; increases need an
+    /// approved `SpendingLimitChange` proposal
+    pub govern_limit_increases: bool,
+    /// Make proposals' required approvers approve in the order listed
+    pub ordered_required_approvers: bool,
+}
+
+/// A signer's approval of a proposal and when it was last confirmed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Approval {
+    pub signer: Pubkey,
+    pub approved_at: i64,
+    pub reason: [u8; 32], // Hash of an off-chain explanation (zeroed if none)
+    pub option: u8, // Option voted for; always 0 on single-option proposals
+}
+
+/// A signer's rejection of a proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Rejection {
+    pub signer: Pubkey,
+    pub reason: [u8; 32], // Hash of an off-chain explanation (zeroed if none)
+}
+
+/// One outcome of a multi-option proposal: a labelled run of the proposal's
+/// instructions, following those of the options before it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct ProposalOption {
+    pub label: [u8; 16],
+    pub instruction_count: u8,
+}
+
+impl Approval {
+    /// Whether the approval was confirmed within the last `validity` seconds;
+    /// always true when `validity` is 0
+    pub fn is_fresh(&self, validity: i64, current_time: i64) -> bool {
+        validity == 0 || current_time < self.approved_at.saturating_add(validity)
+    }
 }
 
-#[derive(Accounts)]
-pub struct ApproveProposal<'info> {
-    #[account(
-        seeds = [b"wallet_config", wallet_config.authority.as_ref()],
-        bump = wallet_config.bump,
-        constraint = wallet_config.is_active
-    )]
-    pub wallet_config: Account<'info, WalletConfig>,
-    
-    #[account(
-        mut,
-        constraint = proposal.status == ProposalStatus::Pending
-    )]
-    pub proposal: Account<'info, Proposal>,
-    
-    pub approver: Signer<'info>,
+/// An oracle price check a proposal must pass before it starts executing.
+/// The oracle account must be passed through `remaining_accounts`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct PriceCondition {
+    pub oracle: Pubkey,
+    /// Byte offset of the little-endian `i64` price in the oracle's data
+    pub price_offset: u16,
+    pub comparison: PriceComparison,
+    pub threshold: i64,
 }
 
-#[derive(Accounts)]
-pub struct ExecuteProposal<'info> {
-    #[account(
-        seeds = [b"wallet_config", wallet_config.authority.as_ref()],
-        bump = wallet_config.bump,
-        constraint = wallet_config.is_active
-    )]
-    pub wallet_config: Account<'info, WalletConfig>,
-    
-    #[account(
-        mut,
-        constraint = proposal.status == ProposalStatus::Approved
-    )]
-    pub proposal: Account<'info, Proposal>,
-    
-    pub executor: Signer<'info>,
+impl PriceCondition {
+    /// Whether the price currently held by `oracle` satisfies the condition
+    pub fn is_met(&self, oracle: &AccountInfo) -> Result<bool> {
+        let data = oracle.try_borrow_data()?;
+        let offset = usize::from(self.price_offset);
+        let price = data
+            .get(offset..offset + 8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(i64::from_le_bytes)
+            .ok_or(MultisigError::InvalidOracleData)?;
+        msg!("Oracle {} price {} against threshold {}", oracle.key, price, self.threshold);
+        Ok(match self.comparison {
+            PriceComparison::GreaterThan => price > self.threshold,
+            PriceComparison::LessThan => price < self.threshold,
+        })
+    }
 }
 
-#[derive(Accounts)]
-pub struct UpdateSigners<'info> {
-    #[account(
-        mut,
-        seeds = [b"wallet_config", wallet_config.authority.as_ref()],
-        bump = wallet_config.bump,
-        constraint = wallet_config.is_active
-    )]
-    pub wallet_config: Account<'info, WalletConfig>,
-    
-    pub approver: Signer<'info>,
+/// Returned by `add_proposal` so callers learn the proposal they created
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct NewProposal {
+    pub id: u64,
+    pub proposal: Pubkey,
 }
 
-#[derive(Accounts)]
-pub struct SetSpendingLimits<'info> {
-    #[account(
-        mut,
-        seeds = [b"wallet_config", wallet_config.authority.as_ref()],
-        bump = wallet_config.bump,
-        constraint = wallet_config.is_active
-    )]
-    pub wallet_config: Account<'info, WalletConfig>,
-    
-    pub approver: Signer<'info>,
+/// Returned by `get_proposal_summary`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ProposalSummary {
+    pub id: u64,
+    pub status: ProposalStatus,
+    pub category: ProposalCategory,
+    pub approvals: u8,
+    pub rejections: u8,
+    pub expiration: i64,
 }
 
-#[derive(Accounts)]
-pub struct DelegateVote<'info> {
-    #[account(
-        mut,
-        seeds = [b"wallet_config", wallet_config.authority.as_ref()],
-        bump = wallet_config.bump,
-        constraint = wallet_config.is_active
-    )]
-    pub wallet_config: Account<'info, WalletConfig>,
-    
-    pub delegator: Signer<'info>,
+/// Spending allowance for proposals carrying `tag`, tracked per spending
+/// period like the wallet's overall limit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Budget {
+    pub tag: [u8; 16],
+    pub limit: u64,
+    pub used: u64,
+    pub last_reset: i64,
 }
 
-#[derive(Accounts)]
-pub struct EmergencyOverride<'info> {
-    #[account(
-        seeds = [b"wallet_config", wallet_config.authority.as_ref()],
-        bump = wallet_config.bump,
-        constraint = wallet_config.is_active
-    )]
-    pub wallet_config: Account<'info, WalletConfig>,
-    
-    pub emergency_authority: Signer<'info>,
+impl Budget {
+    /// Spending counted against the budget at `current_time`
+    pub fn current_used(&self, period: i64, current_time: i64) -> u64 {
+        if current_time >= self.last_reset.saturating_add(period) {
+            0
+        } else {
+            self.used
+        }
+    }
+
+    /// Charge `amount` to the budget, starting a new period if the last one
+    /// has elapsed
+    pub fn charge(&mut self, amount: u64, period: i64, current_time: i64) -> Result<()> {
+        if current_time >= self.last_reset.saturating_add(period) {
+            self.used = 0;
+            self.last_reset = current_time;
+        }
+        let used = self.used.checked_add(amount).ok_or(MultisigError::BudgetExceeded)?;
+        require!(used <= self.limit, MultisigError::BudgetExceeded);
+        self.used = used;
+        Ok(())
+    }
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct WalletConfig {
-    pub authority: Pubkey,
-    #[max_len(10)] // Maximum 10 signers
+/// Returned by `preview_spending`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct SpendingPreview {
+    pub amount: u64,
+    pub projected_spending_used: u64,
+    pub exceeds_limit: bool,
+}
+
+/// A nominated signer waiting to accept with `accept_signer_role`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct PendingSigner {
+    pub address: Pubkey,
+    pub role: MemberRole,
+}
+
+/// Spending limits waiting to be applied by `apply_spending_limit`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct PendingSpendingLimit {
+    pub limit: u64,
+    pub period: i64,
+    pub reset_spending: bool,
+    pub effective_at: i64,
+}
+
+/// A replacement signer set proposed after signer keys were lost
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Recovery {
+    #[max_len(MAX_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub initiated_at: i64,
+    #[max_len(MAX_SIGNERS)] // One approval per current signer
+    pub approvals: Vec<Pubkey>,
+}
+
+/// A replacement signer set awaiting approval from every current signer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct SignerChange {
+    #[max_len(MAX_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    #[max_len(MAX_SIGNERS)] // One approval per current signer
+    pub approvals: Vec<Pubkey>,
+}
+
+/// Wallet settings returned by `export_config` and accepted by
+/// `initialize_wallet_from_snapshot`. Delegations and per-proposal,
+/// spending and pending-change state are not included; the whole snapshot
+/// must fit in transaction return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct WalletSnapshot {
+    pub version: u8,
     pub signers: Vec<Pubkey>,
     pub threshold: u8,
+    pub admin_threshold: u8,
+    pub emergency_threshold: u8,
     pub proposal_timeout: i64,
     pub spending_limit: u64,
     pub spending_period: i64,
-    pub spending_used: u64,
-    pub last_spending_reset: i64,
-    pub is_active: bool,
-    #[max_len(10)] // Maximum 10 members
-    pub members: Vec<Member>,
-    pub proposal_count: u64,
-    pub bump: u8,
+    pub allowed_programs: Vec<Pubkey>,
+    pub quorum: u8,
+    pub voting_mode: VotingMode,
+    pub weight_threshold: u64,
+    pub threshold_bps: u16,
+    pub policy: WalletPolicy,
+    pub guardian: Option<Pubkey>,
+    pub members: Vec<MemberSettings>, // One per signer, in the same order
+    pub budgets: Vec<BudgetLimit>,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct Proposal {
-    pub wallet: Pubkey,
-    pub proposer: Pubkey,
-    #[max_len(50)] // Maximum 50 characters for description
-    pub description: String,
-    pub category: ProposalCategory,
-    #[max_len(3)] // Maximum 3 instructions per proposal
-    pub instructions: Vec<InstructionData>,
-    pub expiration: i64,
-    pub status: ProposalStatus,
-    #[max_len(5)] // Maximum 5 approvals
-    pub approvals: Vec<Pubkey>,
-    #[max_len(5)] // Maximum 5 rejections
-    pub rejections: Vec<Pubkey>,
-    pub created_at: i64,
-    pub executed_at: Option<i64>,
-    pub id: u64,
-    pub bump: u8,
+/// A signer's role and voting weight in a `WalletSnapshot`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct MemberSettings {
+    pub role: MemberRole,
+    pub weight: u64,
+}
+
+/// A tag's spending budget in a `WalletSnapshot`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct BudgetLimit {
+    pub tag: [u8; 16],
+    pub limit: u64,
+}
+
+/// Returned by `member_status`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct MemberStatus {
+    pub is_signer: bool,
+    pub role: Option<MemberRole>,
+    pub is_active: bool,
+    pub delegate: Option<Pubkey>,
+    pub effective_voter: Option<Pubkey>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
@@ -418,17 +4193,220 @@ pub struct Member {
     pub role: MemberRole,
     pub delegate: Option<Pubkey>,
     pub is_active: bool,
+    pub weight: u64,
+    pub approvals_made: u64,
+    pub last_proposal_at: i64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
 pub struct InstructionData {
     pub program_id: Pubkey,
-    #[max_len(3)] // Maximum 3 accounts per instruction
+    #[max_len(MAX_INSTRUCTION_ACCOUNTS)]
     pub accounts: Vec<AccountMeta>,
-    #[max_len(64)] // Maximum 64 bytes for instruction data
+    #[max_len(MAX_INSTRUCTION_DATA_LEN)]
     pub data: Vec<u8>,
 }
 
+impl Proposal {
+    /// Signers who have approved the proposal
+    pub fn approvers(&self) -> Vec<Pubkey> {
+        self.approvals.iter().map(|approval| approval.signer).collect()
+    }
+
+    /// Whether `signer` has approved the proposal
+    pub fn has_approved(&self, signer: &Pubkey) -> bool {
+        self.approvals.iter().any(|approval| approval.signer == *signer)
+    }
+
+    /// Whether `signer` has rejected the proposal
+    pub fn has_rejected(&self, signer: &Pubkey) -> bool {
+        self.rejections.iter().any(|rejection| rejection.signer == *signer)
+    }
+
+    /// Whether `signer` has approved, rejected or abstained on the proposal
+    pub fn has_voted(&self, signer: &Pubkey) -> bool {
+        self.has_approved(signer)
+            || self.has_rejected(signer)
+            || self.abstentions.contains(signer)
+    }
+
+    /// Number of options signers can vote for; 1 for single-option proposals
+    pub fn option_count(&self) -> u8 {
+        u8::try_from(self.options.len().max(1)).unwrap_or(u8::MAX)
+    }
+
+    /// Signers who have approved `option`
+    pub fn option_approvers(&self, option: u8) -> Vec<Pubkey> {
+        self.approvals
+            .iter()
+            .filter(|approval| approval.option == option)
+            .map(|approval| approval.signer)
+            .collect()
+    }
+
+    /// Signers who approved `option` and whose approval was confirmed within
+    /// the last `validity` seconds; all of them when `validity` is 0
+    pub fn fresh_approvers(&self, option: u8, validity: i64, current_time: i64) -> Vec<Pubkey> {
+        self.approvals
+            .iter()
+            .filter(|approval| approval.option == option && approval.is_fresh(validity, current_time))
+            .map(|approval| approval.signer)
+            .collect()
+    }
+
+    /// Whether every required approver is among `approvers`
+    pub fn has_required_approvers(&self, approvers: &[Pubkey]) -> bool {
+        self.required_approvers
+            .iter()
+            .all(|required| approvers.contains(required))
+    }
+
+    /// Whether every required approver listed before `voter` has approved;
+    /// always true for voters who aren't required approvers
+    pub fn approval_in_order(&self, voter: &Pubkey) -> bool {
+        let position = self.required_approvers.iter().position(|required| required == voter);
+        position.is_none_or(|position| {
+            self.required_approvers[..position]
+                .iter()
+                .all(|earlier| self.has_approved(earlier))
+        })
+    }
+
+    /// Mark the proposal approved at `current_time`, recording `option` as
+    /// the winner if it is a multi-option proposal
+    pub fn mark_approved(&mut self, option: u8, current_time: i64) {
+        self.set_status(ProposalStatus::Approved);
+        self.approved_at = Some(current_time);
+        self.selected_option = (!self.options.is_empty()).then_some(option);
+    }
+
+    /// Instructions execution runs: the winning option's run of a
+    /// multi-option proposal, otherwise all of them
+    pub fn executable_instructions(&self) -> &[InstructionData] {
+        let Some(selected) = self.selected_option else {
+            return &self.instructions;
+        };
+        let option_len = |option: &ProposalOption| usize::from(option.instruction_count);
+        let start: usize = self.options.iter().take(usize::from(selected)).map(option_len).sum();
+        let len = self.options.get(usize::from(selected)).map_or(0, option_len);
+        self.instructions.get(start..start + len).unwrap_or_default()
+    }
+
+    /// Move the proposal to `status`, keeping `status_code` in sync
+    pub fn set_status(&mut self, status: ProposalStatus) {
+        self.status_code = status.code();
+        self.status = status;
+    }
+
+    /// Whether the proposal's scheduled start, if any, has been reached
+    pub fn is_active_at(&self, current_time: i64) -> bool {
+        self.not_before.is_none_or(|not_before| current_time >= not_before)
+    }
+
+    /// Time until which an approved proposal can be executed, given the
+    /// policy's `grace_period` past its expiration
+    pub fn executable_until(&self, grace_period: i64) -> i64 {
+        self.expiration.saturating_add(grace_period)
+    }
+
+    /// Whether `delay` seconds have passed since the proposal was approved
+    pub fn timelock_elapsed(&self, delay: i64, current_time: i64) -> bool {
+        self.approved_at
+            .is_some_and(|approved_at| current_time >= approved_at.saturating_add(delay))
+    }
+
+    /// When a pending or approved proposal can be marked expired; approved
+    /// proposals also get the execution `grace_period`. `None` for proposals
+    /// in other states.
+    pub fn expiry_deadline(&self, grace_period: i64) -> Option<i64> {
+        match self.status {
+            ProposalStatus::Pending => Some(self.expiration),
+            ProposalStatus::Approved => Some(self.executable_until(grace_period)),
+            _ => None,
+        }
+    }
+
+    /// Whether at least `period` seconds have passed since submission
+    pub fn voting_period_elapsed(&self, period: i64, current_time: i64) -> bool {
+        current_time >= self.created_at.saturating_add(period)
+    }
+
+    /// Total lamports and token units this proposal moves, or `None` on
+    /// overflow. Until a multi-option proposal has a winner, every option's
+    /// instructions are counted.
+    pub fn spend_amount(&self) -> Option<u64> {
+        let transfer = match self.category {
+            ProposalCategory::TokenTransfer { amount, .. } => amount,
+            ProposalCategory::SolTransfer { lamports, .. } => lamports,
+            _ => 0,
+        };
+        self.executable_instructions()
+            .iter()
+            .try_fold(transfer, |total, instruction| total.checked_add(instruction.spend_amount()))
+    }
+}
+
+impl InstructionData {
+    /// Build the runtime instruction to invoke via CPI
+    pub fn to_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: self
+                .accounts
+                .iter()
+                .map(|meta| anchor_lang::solana_program::instruction::AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+
+    /// Collapse repeated accounts into their first occurrence, which becomes
+    /// a signer or writable if any of the repeats were
+    pub fn merge_duplicate_accounts(&mut self) {
+        let mut merged: Vec<AccountMeta> = Vec::with_capacity(self.accounts.len());
+        for meta in &self.accounts {
+            match merged.iter_mut().find(|existing| existing.pubkey == meta.pubkey) {
+                Some(existing) => {
+                    existing.is_signer |= meta.is_signer;
+                    existing.is_writable |= meta.is_writable;
+                }
+                None => merged.push(meta.clone()),
+            }
+        }
+        self.accounts = merged;
+    }
+
+    /// Lamports or token units moved by this instruction, counted against the
+    /// wallet's spending limit. Instructions that are not System or SPL Token
+    /// transfers are treated as moving nothing.
+    pub fn spend_amount(&self) -> u64 {
+        let read_u64 = |offset: usize| {
+            self.data
+                .get(offset..offset + 8)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(u64::from_le_bytes)
+                .unwrap_or(0)
+        };
+
+        if self.program_id == anchor_lang::system_program::ID {
+            // SystemInstruction::Transfer { lamports }
+            if self.data.get(..4) == Some(&2u32.to_le_bytes()[..]) {
+                return read_u64(4);
+            }
+        } else if self.program_id == anchor_spl::token::ID {
+            // TokenInstruction::Transfer / TransferChecked { amount, .. }
+            if matches!(self.data.first(), Some(3) | Some(12)) {
+                return read_u64(1);
+            }
+        }
+        0
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
 pub struct AccountMeta {
     pub pubkey: Pubkey,
@@ -441,6 +4419,24 @@ pub enum MemberRole {
     Admin,
     Treasurer,
     Member,
+    EmergencyResponder,
+}
+
+impl MemberRole {
+    /// Whether a member with this role may submit proposals of `category`
+    pub fn can_propose(&self, category: &ProposalCategory) -> bool {
+        match category {
+            ProposalCategory::Regular
+            | ProposalCategory::TokenTransfer { .. }
+            | ProposalCategory::SignerChange { .. }
+            | ProposalCategory::SolTransfer { .. }
+            | ProposalCategory::SpendingLimitChange { .. } => true,
+            ProposalCategory::Admin => *self == MemberRole::Admin,
+            ProposalCategory::Emergency => {
+                matches!(self, MemberRole::Admin | MemberRole::EmergencyResponder)
+            }
+        }
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
@@ -448,6 +4444,51 @@ pub enum ProposalCategory {
     Regular,
     Admin,
     Emergency,
+    /// Move `amount` tokens of `mint` from the wallet-owned `source` token
+    /// account to `destination`, or its whole balance if smaller. `amount`
+    /// may not exceed `max_amount`.
+    TokenTransfer {
+        source: Pubkey,
+        destination: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+        max_amount: u64,
+    },
+    /// Replace the wallet's signers and threshold; needs every current
+    /// signer's approval
+    SignerChange {
+        #[max_len(MAX_SIGNERS)]
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+    /// Move `lamports` held by the wallet PDA to `recipient`, keeping the
+    /// PDA rent-exempt
+    SolTransfer {
+        recipient: Pubkey,
+        lamports: u64,
+    },
+    /// Set the wallet's spending limits, resetting the spending window;
+    /// needs the admin threshold
+    SpendingLimitChange {
+        limit: u64,
+        period: i64,
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum PriceComparison {
+    GreaterThan,
+    LessThan,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum VotingMode {
+    /// Each approval counts once against the category threshold
+    Count,
+    /// Approvals count by member weight against `weight_threshold`
+    Weighted,
+    /// Each approval counts once against `threshold_bps` of the signers
+    Proportional,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
@@ -457,11 +4498,130 @@ pub enum ProposalStatus {
     Rejected,
     Executed,
     Expired,
+    Cancelled,
+}
+
+impl ProposalStatus {
+    /// Stable code mirrored in `Proposal::status_code`; unlike the enum's
+    /// serialized form it never changes when variants are added
+    pub fn code(&self) -> u8 {
+        match self {
+            ProposalStatus::Pending => 0,
+            ProposalStatus::Approved => 1,
+            ProposalStatus::Rejected => 2,
+            ProposalStatus::Executed => 3,
+            ProposalStatus::Expired => 4,
+            ProposalStatus::Cancelled => 5,
+        }
+    }
+
+    /// Whether the proposal can no longer change state
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ProposalStatus::Rejected
+                | ProposalStatus::Executed
+                | ProposalStatus::Expired
+                | ProposalStatus::Cancelled
+        )
+    }
+}
+
+/// Emitted when a proposal is submitted
+#[event]
+pub struct ProposalCreated {
+    pub wallet: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub category: ProposalCategory,
+    pub timestamp: i64,
+}
+
+/// Emitted for every approval vote, including the one that meets the threshold.
+/// `approver` is the member whose vote was counted and `delegate` is set when
+/// the vote was cast on their behalf.
+#[event]
+pub struct ProposalApproved {
+    pub wallet: Pubkey,
+    pub proposal_id: u64,
+    pub approver: Pubkey,
+    pub delegate: Option<Pubkey>,
+    pub reason: [u8; 32],
+    pub option: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted for every rejection vote, including the one that rejects the proposal
+#[event]
+pub struct ProposalRejected {
+    pub wallet: Pubkey,
+    pub proposal_id: u64,
+    pub rejector: Pubkey,
+    pub reason: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Emitted when an edit clears a proposal's approvals
+#[event]
+pub struct ProposalReset {
+    pub wallet: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub cleared_approvals: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted for every abstention
+#[event]
+pub struct ProposalAbstained {
+    pub wallet: Pubkey,
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted for each successful `execute_proposal` call and each failure
+/// reported through `record_execution_failure`
+#[event]
+pub struct ExecutionAttempted {
+    pub wallet: Pubkey,
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+    pub success: bool,
+    pub error_code: Option<u32>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the guardian vetoes a proposal
+#[event]
+pub struct ProposalVetoed {
+    pub wallet: Pubkey,
+    pub proposal_id: u64,
+    pub guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted once a proposal's instructions have all been executed
+#[event]
+pub struct ProposalExecuted {
+    pub wallet: Pubkey,
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority executes an emergency override
+#[event]
+pub struct EmergencyOverrideExecuted {
+    pub wallet: Pubkey,
+    pub authority: Pubkey,
+    pub instruction_count: u8,
+    pub timestamp: i64,
 }
 
 #[error_code]
 pub enum MultisigError {
-    #[msg("Invalid threshold - must be greater than 0 and less than or equal to number of signers")]
+    #[msg("Invalid threshold - zero, out of range or not reachable by the current signers")]
     InvalidThreshold,
     #[msg("Invalid timeout - must be greater than 0")]
     InvalidTimeout,
@@ -483,4 +4643,170 @@ pub enum MultisigError {
     AlreadyApproved,
     #[msg("Member not found")]
     MemberNotFound,
+    #[msg("An account referenced by a proposal instruction was not provided")]
+    MissingInstructionAccount,
+    #[msg("Already rejected this proposal")]
+    AlreadyRejected,
+    #[msg("Spending limit exceeded for the current period")]
+    SpendingLimitExceeded,
+    #[msg("Proposal id must match the wallet's next proposal id")]
+    InvalidProposalId,
+    #[msg("Only pending proposals can be cancelled")]
+    ProposalNotCancellable,
+    #[msg("Only executed, rejected, expired or cancelled proposals can be closed")]
+    ProposalNotClosable,
+    #[msg("Proposal has not expired yet")]
+    ProposalNotExpired,
+    #[msg("This member's vote has already been cast directly or through their delegate")]
+    DelegatedApprovalConflict,
+    #[msg("Signers must not contain duplicate keys")]
+    DuplicateSigner,
+    #[msg("Member already exists")]
+    MemberAlreadyExists,
+    #[msg("Wallet already has the maximum number of members")]
+    MemberLimitReached,
+    #[msg("Not enough active signers would remain to meet the threshold")]
+    InsufficientSigners,
+    #[msg("Wallet is already active")]
+    WalletAlreadyActive,
+    #[msg("Proposal description must not be empty")]
+    EmptyDescription,
+    #[msg("Proposal description exceeds 50 bytes")]
+    DescriptionTooLong,
+    #[msg("Proposal expiration is further out than the wallet's proposal timeout")]
+    ExpirationExceedsTimeout,
+    #[msg("Proposal belongs to a different wallet")]
+    WalletMismatch,
+    #[msg("Execution delay since approval has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Proposal can no longer be edited because it has approvals")]
+    ProposalHasApprovals,
+    #[msg("Recovery is not enabled for this wallet")]
+    RecoveryDisabled,
+    #[msg("No recovery is pending")]
+    NoRecoveryPending,
+    #[msg("Recovery does not have enough signer approvals")]
+    RecoveryNotApproved,
+    #[msg("Recovery delay has not elapsed")]
+    RecoveryNotElapsed,
+    #[msg("Current spending period has not elapsed")]
+    SpendingPeriodNotElapsed,
+    #[msg("Too many programs in the allowlist")]
+    TooManyAllowedPrograms,
+    #[msg("Instruction program is not on the wallet's allowlist")]
+    ProgramNotAllowed,
+    #[msg("Requested token amount exceeds the proposal's maximum")]
+    AmountExceedsMax,
+    #[msg("An authority transfer is already pending")]
+    AuthorityTransferPending,
+    #[msg("No authority transfer is pending")]
+    NoAuthorityTransferPending,
+    #[msg("Quorum cannot exceed the number of signers")]
+    InvalidQuorum,
+    #[msg("Instruction references too many distinct accounts")]
+    TooManyInstructionAccounts,
+    #[msg("Too many instructions; each instruction slot adds to every proposal's rent")]
+    TooManyInstructions,
+    #[msg("Only the wallet's guardian can do this")]
+    GuardianOnly,
+    #[msg("Too few approvals are recent enough to execute the proposal")]
+    ApprovalsStale,
+    #[msg("Signer has not approved this proposal")]
+    ApprovalNotFound,
+    #[msg("Member is submitting proposals too quickly")]
+    ProposalRateLimited,
+    #[msg("A wallet needs at least one signer")]
+    NoSigners,
+    #[msg("Too many signers; a wallet holds at most MAX_SIGNERS (10)")]
+    TooManySigners,
+    #[msg("Signer has already voted on this proposal")]
+    AlreadyVoted,
+    #[msg("Proposal's price condition is not met")]
+    ConditionNotMet,
+    #[msg("Oracle account does not hold a price at the expected offset")]
+    InvalidOracleData,
+    #[msg("Admin threshold exceeds the number of signers, so admin proposals could never pass")]
+    UnachievableAdminThreshold,
+    #[msg("Proposal depends on a proposal that has not been executed")]
+    DependencyNotExecuted,
+    #[msg("Too few approvals come from current signers since the signer set changed")]
+    SignerSetChanged,
+    #[msg("Proposal would exceed its tag's spending budget")]
+    BudgetExceeded,
+    #[msg("Too many budgets; a wallet holds at most MAX_BUDGETS (4)")]
+    TooManyBudgets,
+    #[msg("Remaining accounts are not in the order of the instructions being executed")]
+    InstructionOrderMismatch,
+    #[msg("Proposal is scheduled to become executable later")]
+    ProposalNotYetActive,
+    #[msg("Too soon after the last execution; wait for the execution cooldown")]
+    ExecutionCooldownActive,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Wallet snapshot was exported by an unsupported version")]
+    UnsupportedSnapshotVersion,
+    #[msg("Wallet snapshot is malformed")]
+    InvalidSnapshot,
+    #[msg("Spending limits must be changed through propose_spending_limit while a delay is set")]
+    SpendingLimitChangeTimelocked,
+    #[msg("No spending limit change is pending")]
+    NoSpendingLimitChangePending,
+    #[msg("The pending spending limit change is not yet effective")]
+    SpendingLimitDelayNotElapsed,
+    #[msg("Wallet does not hold enough lamports above its rent-exempt minimum")]
+    InsufficientWalletBalance,
+    #[msg("Wallet is executing a proposal; re-entrant calls are not allowed")]
+    ReentrantCall,
+    #[msg("Member has been deactivated")]
+    MemberInactive,
+    #[msg("Proposal instruction data exceeds the maximum length")]
+    InstructionDataTooLarge,
+    #[msg("Proposal does not fit in its account")]
+    ProposalTooLarge,
+    #[msg("Proposal options must split its instructions exactly and suit its category")]
+    InvalidProposalOptions,
+    #[msg("Proposal has no option with this index")]
+    InvalidProposalOption,
+    #[msg("Wallet already has the maximum number of open proposals")]
+    TooManyOpenProposals,
+    #[msg("A signer nomination is already pending")]
+    SignerNominationPending,
+    #[msg("No signer nomination is pending")]
+    NoSignerNominationPending,
+    #[msg("Nominated signer has not accepted its role yet")]
+    PendingSignerNotAccepted,
+    #[msg("Members cannot delegate their vote to themselves")]
+    SelfDelegation,
+    #[msg("Votes cannot be delegated to another signer")]
+    DelegateIsSigner,
+    #[msg("Proposal's minimum voting period has not passed")]
+    VotingPeriodNotElapsed,
+    #[msg("Token account does not hold the proposal's mint")]
+    MintMismatch,
+    #[msg("Source token account is not owned by the wallet")]
+    InvalidTokenOwner,
+    #[msg("Required approvers must be distinct signers")]
+    InvalidRequiredApprovers,
+    #[msg("A required approver has not approved the proposal")]
+    MissingRequiredApprover,
+    #[msg("Execution bounty exceeds the maximum")]
+    ExecutionBountyTooLarge,
+    #[msg("Threshold must require at least one approval")]
+    ThresholdIsZero,
+    #[msg("Threshold is greater than the number of signers")]
+    ThresholdExceedsSigners,
+    #[msg("Spending limit increases must go through a SpendingLimitChange proposal")]
+    SpendingLimitIncreaseRequiresProposal,
+    #[msg("Wallet violates a critical invariant")]
+    InvariantViolated,
+    #[msg("An earlier required approver has not approved yet")]
+    OutOfOrderApproval,
+    #[msg("Account was written by an unsupported program version")]
+    UnsupportedAccountVersion,
+    #[msg("Proposal execution has already started")]
+    ExecutionInProgress,
+    #[msg("An open proposal depends on this proposal")]
+    ProposalHasDependents,
+    #[msg("Too many open proposals are waiting on dependencies")]
+    TooManyAwaitedDependencies,
 }
@@ -1,8 +1,39 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { MultisigDaoWallet } from "../target/types/multisig_dao_wallet";
-import { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+  AccountMeta,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  createTransferInstruction,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
 import { expect } from "chai";
+import { createHash } from "crypto";
+
+const MEMO_PROGRAM_ID = new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+// Loaded from tests/fixtures by Anchor.toml; holds a price of 150_000_000 at offset 0
+const MOCK_ORACLE = new PublicKey("28ffHF3qyKpCqGo6ZZJkKa1aYAi2xJ9wRbukXy3uYRGS");
+// Loaded from tests/fixtures by Anchor.toml; a wallet config with layout version 255
+const FUTURE_VERSION_WALLET = new PublicKey("BYW7m5g9UjnwjoyGVDDpZTtkgWeXr9PcLKXxkw7g43Z3");
+// Loaded from tests/fixtures by Anchor.toml; a wallet config written before
+// accounts were versioned, whose one signer makes its version byte read 1
+const UNVERSIONED_WALLET = new PublicKey("HPgSzgZB9cKskd2djpxEg3DGnYUcRjcdfehYvEM27Hdy");
+const UNVERSIONED_WALLET_AUTHORITY = Keypair.fromSeed(Buffer.from("unversioned-wallet-authority-001"));
+// Loaded from tests/fixtures by Anchor.toml; a version 1 wallet config whose
+// padding holds stale bytes
+const LEGACY_V1_WALLET = new PublicKey("CoDAxMNGiRrZ1GAHfr3ekmt9fMA8Y5hsBJtaMx3GpHZ4");
+const LEGACY_V1_WALLET_AUTHORITY = Keypair.fromSeed(Buffer.from("legacy-wallet-authority-seed-v01"));
 
 describe("multisig-dao-wallet", () => {
   // Configure the client to use the local cluster.
@@ -11,6 +42,278 @@ describe("multisig-dao-wallet", () => {
   const program = anchor.workspace.MultisigDaoWallet as Program<MultisigDaoWallet>;
   const provider = anchor.getProvider();
 
+  // Create a keypair funded for rent and fees
+  const fundedKeypair = async (): Promise<Keypair> => {
+    const keypair = Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(keypair.publicKey, 10 * LAMPORTS_PER_SOL)
+    );
+    return keypair;
+  };
+
+  const findWalletConfig = (walletAuthority: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("wallet_config"), walletAuthority.toBuffer()],
+      program.programId
+    )[0];
+
+  const findProposal = (wallet: PublicKey, proposalId: BN): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), wallet.toBuffer(), proposalId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  // Metadata hash for proposals without an off-chain document
+  const NO_METADATA = new Array(32).fill(0);
+
+  // Tag for proposals without a client-defined label
+  const NO_TAG = new Array(16).fill(0);
+
+  // Reason hash for votes cast without an off-chain explanation
+  const NO_REASON = new Array(32).fill(0);
+
+  // The id (and therefore PDA) the wallet's next proposal will be assigned
+  const nextProposalId = async (wallet: PublicKey): Promise<BN> =>
+    (await program.account.walletConfig.fetch(wallet)).proposalCount;
+
+  // Initialize a wallet owned by `walletAuthority` and return its config PDA.
+  // Category thresholds of 0 fall back to the program defaults, an empty
+  // program allowlist allows any program, and a quorum of 0 disables quorum.
+  const initializeWallet = async (
+    walletAuthority: Keypair,
+    signers: PublicKey[],
+    threshold: number,
+    {
+      spendingLimit = new BN(1000000000),
+      adminThreshold = 0,
+      emergencyThreshold = 0,
+      allowedPrograms = [],
+      quorum = 0,
+    }: {
+      spendingLimit?: BN;
+      adminThreshold?: number;
+      emergencyThreshold?: number;
+      allowedPrograms?: PublicKey[];
+      quorum?: number;
+    } = {}
+  ): Promise<PublicKey> => {
+    const wallet = findWalletConfig(walletAuthority.publicKey);
+    await program.methods
+      .initializeWallet(
+        signers,
+        threshold,
+        new BN(3600),
+        spendingLimit,
+        new BN(86400),
+        adminThreshold,
+        emergencyThreshold,
+        allowedPrograms,
+        quorum
+      )
+      .accounts({
+        walletConfig: wallet,
+        authority: walletAuthority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([walletAuthority])
+      .rpc();
+    return wallet;
+  };
+
+  // Convert a web3 instruction into the program's InstructionData layout
+  const toInstructionData = (ix: TransactionInstruction) => ({
+    programId: ix.programId,
+    accounts: ix.keys.map((key) => ({ pubkey: key.pubkey, isSigner: key.isSigner, isWritable: key.isWritable })),
+    data: ix.data,
+  });
+
+  const sleep = (seconds: number) => new Promise((resolve) => setTimeout(resolve, seconds * 1000));
+
+  // Submit a proposal expiring in `expiresIn` seconds and return its PDA
+  const createProposal = async (
+    wallet: PublicKey,
+    proposer: Keypair,
+    category: any = { regular: {} },
+    instructions: any[] = [],
+    expiresIn: number = 1800,
+    description: string = "Test proposal",
+    options: any[] = [],
+    requiredApprovers: PublicKey[] = []
+  ): Promise<PublicKey> => {
+    const proposalId = await nextProposalId(wallet);
+    const proposal = findProposal(wallet, proposalId);
+    const expiration = new BN(Math.floor(Date.now() / 1000) + expiresIn);
+    await program.methods
+      .addProposal(proposalId, description, NO_METADATA, NO_TAG, category, instructions, expiration, null, null, null, options, requiredApprovers)
+      .accounts({
+        proposal,
+        walletConfig: wallet,
+        proposer: proposer.publicKey,
+        feePayer: proposer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([proposer])
+      .rpc();
+    return proposal;
+  };
+
+  const expireProposal = async (wallet: PublicKey, proposal: PublicKey) => {
+    await program.methods
+      .expireProposal()
+      .accounts({
+        walletConfig: wallet,
+        proposal,
+      })
+      .rpc();
+  };
+
+  const cancelProposal = async (wallet: PublicKey, proposal: PublicKey, proposer: Keypair) => {
+    await program.methods
+      .cancelProposal()
+      .accounts({
+        walletConfig: wallet,
+        proposal,
+        proposer: proposer.publicKey,
+      })
+      .signers([proposer])
+      .rpc();
+  };
+
+  const closeProposal = async (wallet: PublicKey, proposal: PublicKey, feePayer: PublicKey) => {
+    await program.methods
+      .closeProposal()
+      .accounts({
+        walletConfig: wallet,
+        proposal,
+        feePayer,
+      })
+      .rpc();
+  };
+
+  const executeProposal = async (
+    wallet: PublicKey,
+    proposal: PublicKey,
+    executor: Keypair,
+    remainingAccounts: AccountMeta[] = [],
+    maxInstructions: number = 0
+  ) => {
+    await program.methods
+      .executeProposal(maxInstructions)
+      .accounts({
+        walletConfig: wallet,
+        proposal,
+        executor: executor.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([executor])
+      .rpc();
+  };
+
+  const delegateVote = async (wallet: PublicKey, delegator: Keypair, delegate: PublicKey) => {
+    await program.methods
+      .delegateVote(delegate)
+      .accounts({
+        walletConfig: wallet,
+        delegator: delegator.publicKey,
+      })
+      .signers([delegator])
+      .rpc();
+  };
+
+  const updateSigners = async (
+    wallet: PublicKey,
+    newSigners: PublicKey[],
+    newThreshold: number,
+    approver: Keypair,
+    remainingAccounts: AccountMeta[] = []
+  ) => {
+    await program.methods
+      .updateSigners(newSigners, newThreshold)
+      .accounts({
+        walletConfig: wallet,
+        approver: approver.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([approver])
+      .rpc();
+  };
+
+  const addMember = async (wallet: PublicKey, address: PublicKey, role: any, admin: Keypair) => {
+    await program.methods
+      .addMember(address, role)
+      .accounts({
+        walletConfig: wallet,
+        admin: admin.publicKey,
+      })
+      .signers([admin])
+      .rpc();
+  };
+
+  const removeMember = async (
+    wallet: PublicKey,
+    address: PublicKey,
+    admin: Keypair,
+    remainingAccounts: AccountMeta[] = []
+  ) => {
+    await program.methods
+      .removeMember(address)
+      .accounts({
+        walletConfig: wallet,
+        admin: admin.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([admin])
+      .rpc();
+  };
+
+  const rejectProposal = async (
+    wallet: PublicKey,
+    proposal: PublicKey,
+    rejector: Keypair,
+    reason: number[] = NO_REASON
+  ) => {
+    await program.methods
+      .rejectProposal(reason)
+      .accounts({
+        walletConfig: wallet,
+        proposal,
+        rejector: rejector.publicKey,
+      })
+      .signers([rejector])
+      .rpc();
+  };
+
+  const approveProposal = async (
+    wallet: PublicKey,
+    proposal: PublicKey,
+    approver: Keypair,
+    reason: number[] = NO_REASON,
+    option: number = 0
+  ) => {
+    await program.methods
+      .approveProposal(reason, option)
+      .accounts({
+        walletConfig: wallet,
+        proposal,
+        approver: approver.publicKey,
+      })
+      .signers([approver])
+      .rpc();
+  };
+
+  // Update some of the wallet's policy fields, keeping the rest unchanged
+  const setPolicy = async (wallet: PublicKey, walletAuthority: Keypair, changes: object) => {
+    const { policy } = await program.account.walletConfig.fetch(wallet);
+    await program.methods
+      .setPolicy({ ...policy, ...changes })
+      .accounts({
+        walletConfig: wallet,
+        authority: walletAuthority.publicKey,
+      })
+      .signers([walletAuthority])
+      .rpc();
+  };
+
   // Test accounts
   let authority: Keypair;
   let signer1: Keypair;
@@ -59,7 +362,7 @@ describe("multisig-dao-wallet", () => {
       const spendingPeriod = new BN(86400); // 24 hours
 
       const tx = await program.methods
-        .initializeWallet(signers, threshold, proposalTimeout, spendingLimit, spendingPeriod)
+        .initializeWallet(signers, threshold, proposalTimeout, spendingLimit, spendingPeriod, 0, 0, [], 0)
         .accounts({
           walletConfig,
           authority: authority.publicKey,
@@ -86,7 +389,7 @@ describe("multisig-dao-wallet", () => {
 
       try {
         await program.methods
-          .initializeWallet(signers, threshold, new BN(3600), new BN(1000000000), new BN(86400))
+          .initializeWallet(signers, threshold, new BN(3600), new BN(1000000000), new BN(86400), 0, 0, [], 0)
           .accounts({
             walletConfig: PublicKey.findProgramAddressSync(
               [Buffer.from("wallet_config"), nonSigner.publicKey.toBuffer()],
@@ -110,7 +413,7 @@ describe("multisig-dao-wallet", () => {
 
       try {
         await program.methods
-          .initializeWallet(signers, threshold, new BN(3600), new BN(1000000000), new BN(86400))
+          .initializeWallet(signers, threshold, new BN(3600), new BN(1000000000), new BN(86400), 0, 0, [], 0)
           .accounts({
             walletConfig: PublicKey.findProgramAddressSync(
               [Buffer.from("wallet_config"), nonSigner.publicKey.toBuffer()],
@@ -127,6 +430,92 @@ describe("multisig-dao-wallet", () => {
         expect(error.toString()).to.match(/InvalidThreshold|Invalid|Error/);
       }
     });
+
+    it("Should report a threshold above the number of signers", async () => {
+      const walletAuthority = await fundedKeypair();
+
+      try {
+        await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 3);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ThresholdExceedsSigners/);
+      }
+    });
+
+    it("Should report a zero threshold", async () => {
+      const walletAuthority = await fundedKeypair();
+
+      try {
+        await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 0);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ThresholdIsZero/);
+      }
+    });
+
+    it("Should fail with duplicate signers", async () => {
+      const walletAuthority = await fundedKeypair();
+      const signers = [walletAuthority.publicKey, signer1.publicKey, signer1.publicKey];
+
+      try {
+        await initializeWallet(walletAuthority, signers, 2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/DuplicateSigner/);
+      }
+    });
+
+    it("Should fail with the authority listed twice", async () => {
+      const walletAuthority = await fundedKeypair();
+      const signers = [walletAuthority.publicKey, signer1.publicKey, walletAuthority.publicKey];
+
+      try {
+        await initializeWallet(walletAuthority, signers, 2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/DuplicateSigner/);
+      }
+    });
+
+    it("Should fail with no signers", async () => {
+      const walletAuthority = await fundedKeypair();
+
+      try {
+        await initializeWallet(walletAuthority, [], 1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NoSigners/);
+      }
+    });
+
+    it("Should initialize with the maximum number of signers", async () => {
+      const walletAuthority = await fundedKeypair();
+      const signers = [walletAuthority.publicKey];
+      while (signers.length < 10) {
+        signers.push(Keypair.generate().publicKey);
+      }
+
+      const wallet = await initializeWallet(walletAuthority, signers, 2);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.signers.length).to.equal(10);
+      expect(walletConfigAccount.members.length).to.equal(10);
+    });
+
+    it("Should fail with more than the maximum number of signers", async () => {
+      const walletAuthority = await fundedKeypair();
+      const signers = [walletAuthority.publicKey];
+      while (signers.length < 11) {
+        signers.push(Keypair.generate().publicKey);
+      }
+
+      try {
+        await initializeWallet(walletAuthority, signers, 2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/TooManySigners/);
+      }
+    });
   });
 
   describe("Proposal Management", () => {
@@ -144,7 +533,7 @@ describe("multisig-dao-wallet", () => {
 
       try {
         await program.methods
-          .initializeWallet(signers, threshold, proposalTimeout, spendingLimit, spendingPeriod)
+          .initializeWallet(signers, threshold, proposalTimeout, spendingLimit, spendingPeriod, 0, 0, [], 0)
           .accounts({
             walletConfig,
             authority: authority.publicKey,
@@ -156,34 +545,24 @@ describe("multisig-dao-wallet", () => {
         // Wallet might already be initialized, ignore error
         console.log("Wallet already initialized");
       }
-
-      // Pre-generate unique proposal PDAs for each test
-      [proposal1] = PublicKey.findProgramAddressSync(
-        [Buffer.from("proposal"), walletConfig.toBuffer(), signer1.publicKey.toBuffer()],
-        program.programId
-      );
-      [proposal2] = PublicKey.findProgramAddressSync(
-        [Buffer.from("proposal"), walletConfig.toBuffer(), signer2.publicKey.toBuffer()],
-        program.programId
-      );
-      [proposal3] = PublicKey.findProgramAddressSync(
-        [Buffer.from("proposal"), walletConfig.toBuffer(), signer3.publicKey.toBuffer()],
-        program.programId
-      );
     });
 
     it("Should create a proposal", async () => {
       const description = "Test proposal for multisig wallet";
       const category = { regular: {} };
       const instructions: any[] = [];
-      const expiration = new BN(Math.floor(Date.now() / 1000) + 3600); // 1 hour from now
+      const expiration = new BN(Math.floor(Date.now() / 1000) + 1800); // 30 minutes from now
+
+      const proposalId = await nextProposalId(walletConfig);
+      proposal1 = findProposal(walletConfig, proposalId);
 
       const tx = await program.methods
-        .addProposal(description, category, instructions, expiration)
+        .addProposal(proposalId, description, NO_METADATA, NO_TAG, category, instructions, expiration, null, null, null, [], [])
         .accounts({
           proposal: proposal1,
           walletConfig,
           proposer: signer1.publicKey,
+          feePayer: signer1.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([signer1])
@@ -205,14 +584,18 @@ describe("multisig-dao-wallet", () => {
       const description = "Test proposal for approval";
       const category = { regular: {} };
       const instructions: any[] = [];
-      const expiration = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const expiration = new BN(Math.floor(Date.now() / 1000) + 1800);
+
+      const proposalId = await nextProposalId(walletConfig);
+      proposal2 = findProposal(walletConfig, proposalId);
 
       await program.methods
-        .addProposal(description, category, instructions, expiration)
+        .addProposal(proposalId, description, NO_METADATA, NO_TAG, category, instructions, expiration, null, null, null, [], [])
         .accounts({
           proposal: proposal2,
           walletConfig,
           proposer: signer2.publicKey,
+          feePayer: signer2.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([signer2])
@@ -220,7 +603,7 @@ describe("multisig-dao-wallet", () => {
 
       // Approve the proposal
       const tx = await program.methods
-        .approveProposal()
+        .approveProposal(NO_REASON, 0)
         .accounts({
           walletConfig,
           proposal: proposal2,
@@ -234,7 +617,7 @@ describe("multisig-dao-wallet", () => {
       // Verify approval
       const proposalAccount = await program.account.proposal.fetch(proposal2);
       expect(proposalAccount.approvals.length).to.equal(1);
-      expect(proposalAccount.approvals[0].toString()).to.equal(signer1.publicKey.toString());
+      expect(proposalAccount.approvals[0].signer.toString()).to.equal(signer1.publicKey.toString());
     });
 
     it("Should fail to approve with non-signer", async () => {
@@ -242,14 +625,18 @@ describe("multisig-dao-wallet", () => {
       const description = "Test proposal for non-signer approval";
       const category = { regular: {} };
       const instructions: any[] = [];
-      const expiration = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const expiration = new BN(Math.floor(Date.now() / 1000) + 1800);
+
+      const proposalId = await nextProposalId(walletConfig);
+      proposal3 = findProposal(walletConfig, proposalId);
 
       await program.methods
-        .addProposal(description, category, instructions, expiration)
+        .addProposal(proposalId, description, NO_METADATA, NO_TAG, category, instructions, expiration, null, null, null, [], [])
         .accounts({
           proposal: proposal3,
           walletConfig,
           proposer: signer3.publicKey,
+          feePayer: signer3.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([signer3])
@@ -258,7 +645,7 @@ describe("multisig-dao-wallet", () => {
       // Try to approve with non-signer
       try {
         await program.methods
-          .approveProposal()
+          .approveProposal(NO_REASON, 0)
           .accounts({
             walletConfig,
             proposal: proposal3,
@@ -275,23 +662,22 @@ describe("multisig-dao-wallet", () => {
 
     it("Should execute an approved proposal", async () => {
       // Generate a new proposal PDA for execution test
-      const [execProposal] = PublicKey.findProgramAddressSync(
-        [Buffer.from("proposal"), walletConfig.toBuffer(), authority.publicKey.toBuffer()],
-        program.programId
-      );
+      const proposalId = await nextProposalId(walletConfig);
+      const execProposal = findProposal(walletConfig, proposalId);
 
       // Create and approve a proposal
       const description = "Test proposal for execution";
       const category = { regular: {} };
       const instructions: any[] = [];
-      const expiration = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const expiration = new BN(Math.floor(Date.now() / 1000) + 1800);
 
       await program.methods
-        .addProposal(description, category, instructions, expiration)
+        .addProposal(proposalId, description, NO_METADATA, NO_TAG, category, instructions, expiration, null, null, null, [], [])
         .accounts({
           proposal: execProposal,
           walletConfig,
           proposer: authority.publicKey,
+          feePayer: authority.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([authority])
@@ -299,7 +685,7 @@ describe("multisig-dao-wallet", () => {
 
       // Approve with enough signers to meet threshold
       await program.methods
-        .approveProposal()
+        .approveProposal(NO_REASON, 0)
         .accounts({
           walletConfig,
           proposal: execProposal,
@@ -309,7 +695,7 @@ describe("multisig-dao-wallet", () => {
         .rpc();
 
       await program.methods
-        .approveProposal()
+        .approveProposal(NO_REASON, 0)
         .accounts({
           walletConfig,
           proposal: execProposal,
@@ -320,7 +706,7 @@ describe("multisig-dao-wallet", () => {
 
       // Execute the proposal
       const tx = await program.methods
-        .executeProposal()
+        .executeProposal(0)
         .accounts({
           walletConfig,
           proposal: execProposal,
@@ -349,7 +735,7 @@ describe("multisig-dao-wallet", () => {
 
       try {
         await program.methods
-          .initializeWallet(signers, threshold, proposalTimeout, spendingLimit, spendingPeriod)
+          .initializeWallet(signers, threshold, proposalTimeout, spendingLimit, spendingPeriod, 0, 0, [], 0)
           .accounts({
             walletConfig,
             authority: authority.publicKey,
@@ -366,16 +752,17 @@ describe("multisig-dao-wallet", () => {
       const newSigners = [authority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey, nonSigner.publicKey];
       const newThreshold = 3;
 
-      const tx = await program.methods
-        .updateSigners(newSigners, newThreshold)
-        .accounts({
-          walletConfig,
-          approver: authority.publicKey,
-        })
-        .signers([authority])
-        .rpc();
-
-      console.log("Update signers transaction:", tx);
+      // Every current signer has to approve the change
+      for (const approver of [authority, signer1, signer2, signer3]) {
+        await program.methods
+          .updateSigners(newSigners, newThreshold)
+          .accounts({
+            walletConfig,
+            approver: approver.publicKey,
+          })
+          .signers([approver])
+          .rpc();
+      }
 
       // Verify update
       const walletConfigAccount = await program.account.walletConfig.fetch(walletConfig);
@@ -436,7 +823,7 @@ describe("multisig-dao-wallet", () => {
 
       try {
         await program.methods
-          .initializeWallet(signers, threshold, proposalTimeout, spendingLimit, spendingPeriod)
+          .initializeWallet(signers, threshold, proposalTimeout, spendingLimit, spendingPeriod, 0, 0, [], 0)
           .accounts({
             walletConfig,
             authority: authority.publicKey,
@@ -483,4 +870,5002 @@ describe("multisig-dao-wallet", () => {
       }
     });
   });
+
+  describe("Proposal Execution", () => {
+    let execAuthority: Keypair;
+    let execWallet: PublicKey;
+    let execProposal: PublicKey;
+
+    before(async () => {
+      execAuthority = await fundedKeypair();
+      execWallet = await initializeWallet(
+        execAuthority,
+        [execAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+
+      // A memo instruction that the wallet PDA must sign
+      const instructions = [
+        {
+          programId: MEMO_PROGRAM_ID,
+          accounts: [{ pubkey: execWallet, isSigner: true, isWritable: false }],
+          data: Buffer.from("multisig memo"),
+        },
+      ];
+      execProposal = await createProposal(execWallet, execAuthority, { regular: {} }, instructions);
+
+      await approveProposal(execWallet, execProposal, signer1);
+      await approveProposal(execWallet, execProposal, signer2);
+    });
+
+    it("Should fail to execute when instruction accounts are missing", async () => {
+      try {
+        await program.methods
+          .executeProposal(0)
+          .accounts({
+            walletConfig: execWallet,
+            proposal: execProposal,
+            executor: signer1.publicKey,
+          })
+          .signers([signer1])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MissingInstructionAccount/);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(execProposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should fail to execute with a non-signer", async () => {
+      try {
+        await executeProposal(execWallet, execProposal, nonSigner, [
+          { pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false },
+        ]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized|not authorized|unauthorized/i);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(execProposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should invoke proposal instructions signed by the wallet PDA", async () => {
+      const tx = await program.methods
+        .executeProposal(0)
+        .accounts({
+          walletConfig: execWallet,
+          proposal: execProposal,
+          executor: signer1.publicKey,
+        })
+        .remainingAccounts([{ pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false }])
+        .signers([signer1])
+        .rpc({ commitment: "confirmed" });
+
+      const proposalAccount = await program.account.proposal.fetch(execProposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+      expect(proposalAccount.executedAt).to.not.be.null;
+
+      // The memo program only logs the memo if the wallet PDA's signature checked out
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      expect(txInfo.meta.logMessages.some((log) => log.includes('"multisig memo"'))).to.be.true;
+    });
+
+    it("Should execute a large proposal across several calls", async () => {
+      const instructions = [1, 2, 3, 4, 5, 6].map((n) => ({
+        programId: MEMO_PROGRAM_ID,
+        accounts: [{ pubkey: execWallet, isSigner: true, isWritable: false }],
+        data: Buffer.from(`memo ${n}`),
+      }));
+      const proposal = await createProposal(execWallet, execAuthority, { regular: {} }, instructions);
+      await approveProposal(execWallet, proposal, signer1);
+      await approveProposal(execWallet, proposal, signer2);
+      const memoAccounts = [{ pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false }];
+
+      await executeProposal(execWallet, proposal, signer1, memoAccounts, 3);
+
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.executedInstructionCount).to.equal(3);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+
+      await executeProposal(execWallet, proposal, signer1, memoAccounts, 3);
+
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.executedInstructionCount).to.equal(6);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should invoke instructions in their stored order", async () => {
+      const labels = ["first", "second", "third"];
+      const instructions = labels.map((label) => ({
+        programId: MEMO_PROGRAM_ID,
+        accounts: [{ pubkey: execWallet, isSigner: true, isWritable: false }],
+        data: Buffer.from(label),
+      }));
+      const proposal = await createProposal(execWallet, execAuthority, { regular: {} }, instructions);
+      await approveProposal(execWallet, proposal, signer1);
+      await approveProposal(execWallet, proposal, signer2);
+
+      const tx = await program.methods
+        .executeProposal(0)
+        .accounts({
+          walletConfig: execWallet,
+          proposal,
+          executor: signer1.publicKey,
+        })
+        .remainingAccounts([{ pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false }])
+        .signers([signer1])
+        .rpc({ commitment: "confirmed" });
+
+      // The memo program logs each memo as it is invoked
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const logs = txInfo.meta.logMessages;
+      const positions = labels.map((label) => logs.findIndex((log) => log.includes(`"${label}"`)));
+      expect(positions.every((position) => position >= 0)).to.be.true;
+      expect(positions).to.deep.equal([...positions].sort((a, b) => a - b));
+    });
+
+    it("Should reject accounts supplied out of instruction order", async () => {
+      const first = Keypair.generate().publicKey;
+      const second = Keypair.generate().publicKey;
+      const instructions = [first, second].map((toPubkey) =>
+        toInstructionData(SystemProgram.transfer({ fromPubkey: signer1.publicKey, toPubkey, lamports: 1000 }))
+      );
+      const proposal = await createProposal(execWallet, execAuthority, { regular: {} }, instructions);
+      await approveProposal(execWallet, proposal, signer1);
+      await approveProposal(execWallet, proposal, signer2);
+
+      try {
+        await executeProposal(execWallet, proposal, signer1, [
+          { pubkey: signer1.publicKey, isSigner: true, isWritable: true },
+          { pubkey: second, isSigner: false, isWritable: true },
+          { pubkey: first, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InstructionOrderMismatch/);
+      }
+    });
+  });
+
+  describe("Category Thresholds", () => {
+    it("Should approve an emergency proposal with one vote when threshold is 1", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      const proposal = await createProposal(wallet, walletAuthority, { emergency: {} });
+
+      await approveProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should keep default thresholds within bounds at a threshold of 1", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.emergencyThreshold).to.equal(1);
+      expect(walletConfigAccount.adminThreshold).to.equal(2);
+    });
+
+    it("Should cap the admin threshold at the number of signers", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 2);
+      const proposal = await createProposal(wallet, walletAuthority, { admin: {} });
+
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should use each category's configured threshold", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey],
+        2,
+        { adminThreshold: 4, emergencyThreshold: 3 }
+      );
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.adminThreshold).to.equal(4);
+      expect(walletConfigAccount.emergencyThreshold).to.equal(3);
+
+      const regular = await createProposal(wallet, walletAuthority, { regular: {} });
+      const emergency = await createProposal(wallet, walletAuthority, { emergency: {} });
+      const admin = await createProposal(wallet, walletAuthority, { admin: {} });
+
+      for (const approver of [walletAuthority, signer1]) {
+        await approveProposal(wallet, regular, approver);
+        await approveProposal(wallet, emergency, approver);
+        await approveProposal(wallet, admin, approver);
+      }
+      expect((await program.account.proposal.fetch(regular)).status).to.deep.equal({ approved: {} });
+      expect((await program.account.proposal.fetch(emergency)).status).to.deep.equal({ pending: {} });
+
+      await approveProposal(wallet, emergency, signer2);
+      await approveProposal(wallet, admin, signer2);
+      expect((await program.account.proposal.fetch(emergency)).status).to.deep.equal({ approved: {} });
+      expect((await program.account.proposal.fetch(admin)).status).to.deep.equal({ pending: {} });
+
+      await approveProposal(wallet, admin, signer3);
+      expect((await program.account.proposal.fetch(admin)).status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should fail with a category threshold above the number of signers", async () => {
+      const walletAuthority = await fundedKeypair();
+
+      try {
+        await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1, {
+          emergencyThreshold: 3,
+        });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidThreshold/);
+      }
+    });
+
+    it("Should fail with an admin threshold no signer set could reach", async () => {
+      const walletAuthority = await fundedKeypair();
+
+      try {
+        await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 2, {
+          adminThreshold: 3,
+        });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/UnachievableAdminThreshold/);
+      }
+    });
+
+    it("Should cap the default admin threshold when every signer is required", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 2);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.adminThreshold).to.equal(2);
+
+      const admin = await createProposal(wallet, walletAuthority, { admin: {} });
+      await approveProposal(wallet, admin, walletAuthority);
+      await approveProposal(wallet, admin, signer1);
+      expect((await program.account.proposal.fetch(admin)).status).to.deep.equal({ approved: {} });
+    });
+  });
+
+  describe("Proposal Rejection", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+    let proposal: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+      proposal = await createProposal(wallet, walletAuthority);
+    });
+
+    it("Should record a rejection while approval is still possible", async () => {
+      await rejectProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.rejections.length).to.equal(1);
+      expect(proposalAccount.rejections[0].signer.toString()).to.equal(signer1.publicKey.toString());
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should fail to reject twice", async () => {
+      try {
+        await rejectProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/AlreadyRejected/);
+      }
+    });
+
+    it("Should fail to reject with non-signer", async () => {
+      try {
+        await rejectProposal(wallet, proposal, nonSigner);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized|not authorized|unauthorized/i);
+      }
+    });
+
+    it("Should mark the proposal rejected once the threshold is unreachable", async () => {
+      await rejectProposal(wallet, proposal, signer2);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.rejections.length).to.equal(2);
+      expect(proposalAccount.status).to.deep.equal({ rejected: {} });
+    });
+  });
+
+  describe("Spending Limits", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+    let recipient: Keypair;
+
+    // A transfer funded by signer1, who must execute so it can sign the inner transfer
+    const transferInstruction = (lamports: number) =>
+      toInstructionData(
+        SystemProgram.transfer({
+          fromPubkey: signer1.publicKey,
+          toPubkey: recipient.publicKey,
+          lamports,
+        })
+      );
+
+    const transferAccounts = (): AccountMeta[] => [
+      { pubkey: signer1.publicKey, isSigner: true, isWritable: true },
+      { pubkey: recipient.publicKey, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ];
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      recipient = Keypair.generate();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey],
+        1,
+        { spendingLimit: new BN(LAMPORTS_PER_SOL) }
+      );
+    });
+
+    it("Should charge executed transfers against the spending limit", async () => {
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [
+        transferInstruction(0.6 * LAMPORTS_PER_SOL),
+      ]);
+      await approveProposal(wallet, proposal, walletAuthority);
+      await executeProposal(wallet, proposal, signer1, transferAccounts());
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.spendingUsed.toNumber()).to.equal(0.6 * LAMPORTS_PER_SOL);
+      expect(await provider.connection.getBalance(recipient.publicKey)).to.equal(0.6 * LAMPORTS_PER_SOL);
+    });
+
+    it("Should reject execution that exceeds the spending limit", async () => {
+      const proposal = await createProposal(wallet, signer1, { regular: {} }, [
+        transferInstruction(0.6 * LAMPORTS_PER_SOL),
+      ]);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      try {
+        await executeProposal(wallet, proposal, signer1, transferAccounts());
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/SpendingLimitExceeded/);
+      }
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.spendingUsed.toNumber()).to.equal(0.6 * LAMPORTS_PER_SOL);
+    });
+
+    it("Should preview the spending that execution then records", async () => {
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [
+        transferInstruction(0.3 * LAMPORTS_PER_SOL),
+      ]);
+
+      const preview = await program.methods
+        .previewSpending()
+        .accounts({ walletConfig: wallet, proposal })
+        .view();
+      expect(preview.amount.toNumber()).to.equal(0.3 * LAMPORTS_PER_SOL);
+      expect(preview.projectedSpendingUsed.toNumber()).to.equal(0.9 * LAMPORTS_PER_SOL);
+      expect(preview.exceedsLimit).to.be.false;
+
+      await approveProposal(wallet, proposal, walletAuthority);
+      await executeProposal(wallet, proposal, signer1, transferAccounts());
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.spendingUsed.toNumber()).to.equal(preview.projectedSpendingUsed.toNumber());
+    });
+
+    it("Should preview a spend that would exceed the limit", async () => {
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [
+        transferInstruction(0.3 * LAMPORTS_PER_SOL),
+      ]);
+
+      const preview = await program.methods
+        .previewSpending()
+        .accounts({ walletConfig: wallet, proposal })
+        .view();
+
+      expect(preview.projectedSpendingUsed.toNumber()).to.equal(1.2 * LAMPORTS_PER_SOL);
+      expect(preview.exceedsLimit).to.be.true;
+    });
+  });
+
+  describe("Concurrent Proposals", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should let the same proposer open several proposals", async () => {
+      const proposals: PublicKey[] = [];
+      for (let i = 0; i < 3; i++) {
+        proposals.push(await createProposal(wallet, signer1));
+      }
+
+      for (let i = 0; i < 3; i++) {
+        const proposalAccount = await program.account.proposal.fetch(proposals[i]);
+        expect(proposalAccount.id.toNumber()).to.equal(i);
+        expect(proposalAccount.proposer.toString()).to.equal(signer1.publicKey.toString());
+        expect(proposalAccount.status).to.deep.equal({ pending: {} });
+      }
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.proposalCount.toNumber()).to.equal(3);
+    });
+
+    it("Should fail with a proposal id other than the next one", async () => {
+      const proposalId = (await nextProposalId(wallet)).addn(1);
+
+      try {
+        await program.methods
+          .addProposal(proposalId, "Skipped id", NO_METADATA, NO_TAG, { regular: {} }, [], new BN(Math.floor(Date.now() / 1000) + 1800), null, null, null, [], [])
+          .accounts({
+            proposal: findProposal(wallet, proposalId),
+            walletConfig: wallet,
+            proposer: signer1.publicKey,
+            feePayer: signer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([signer1])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidProposalId/);
+      }
+    });
+
+    it("Should fail with the largest possible proposal id", async () => {
+      const proposalId = new BN("18446744073709551615"); // u64::MAX
+
+      try {
+        await program.methods
+          .addProposal(proposalId, "Max id", NO_METADATA, NO_TAG, { regular: {} }, [], new BN(Math.floor(Date.now() / 1000) + 1800), null, null, null, [], [])
+          .accounts({
+            proposal: findProposal(wallet, proposalId),
+            walletConfig: wallet,
+            proposer: signer1.publicKey,
+            feePayer: signer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([signer1])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidProposalId/);
+      }
+    });
+  });
+
+  describe("Proposal Cancellation", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should let the proposer cancel their pending proposal", async () => {
+      const proposal = await createProposal(wallet, signer1);
+
+      await cancelProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ cancelled: {} });
+    });
+
+    it("Should fail to cancel another signer's proposal", async () => {
+      const proposal = await createProposal(wallet, signer1);
+
+      try {
+        await cancelProposal(wallet, proposal, signer2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized|not authorized|unauthorized/i);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should fail to cancel an approved proposal", async () => {
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, signer1);
+      await approveProposal(wallet, proposal, signer2);
+
+      try {
+        await cancelProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalNotCancellable/);
+      }
+    });
+  });
+
+  describe("Proposal Closing", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should record who executed a proposal", async () => {
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      await executeProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.executedBy.toString()).to.equal(signer1.publicKey.toString());
+      expect(proposalAccount.executedAt).to.not.be.null;
+    });
+
+    it("Should close an executed proposal and refund the proposer", async () => {
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, walletAuthority);
+      await executeProposal(wallet, proposal, walletAuthority);
+
+      const balanceBefore = await provider.connection.getBalance(signer1.publicKey);
+      await closeProposal(wallet, proposal, signer1.publicKey);
+
+      expect(await provider.connection.getAccountInfo(proposal)).to.be.null;
+      const balanceAfter = await provider.connection.getBalance(signer1.publicKey);
+      expect(balanceAfter).to.be.greaterThan(balanceBefore);
+    });
+
+    it("Should fail to close a pending proposal", async () => {
+      const proposal = await createProposal(wallet, signer1);
+
+      try {
+        await closeProposal(wallet, proposal, signer1.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalNotClosable/);
+      }
+    });
+
+    it("Should fail to refund anyone but the fee payer", async () => {
+      const proposal = await createProposal(wallet, signer1);
+      await cancelProposal(wallet, proposal, signer1);
+
+      try {
+        await closeProposal(wallet, proposal, walletAuthority.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ConstraintAddress|address constraint/i);
+      }
+    });
+
+    it("Should never reuse a closed proposal's address", async () => {
+      const closed = await createProposal(wallet, signer1);
+      await cancelProposal(wallet, closed, signer1);
+      await closeProposal(wallet, closed, signer1.publicKey);
+
+      const recreated = await createProposal(wallet, signer1);
+
+      expect(recreated.toString()).to.not.equal(closed.toString());
+      expect(await provider.connection.getAccountInfo(closed)).to.be.null;
+      const closedId = (await program.account.proposal.fetch(recreated)).id.subn(1);
+      expect(findProposal(wallet, closedId).toString()).to.equal(closed.toString());
+    });
+  });
+
+  describe("Proposal Events", () => {
+    it("Should emit a ProposalCreated event", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+
+      let listener: number;
+      const event = new Promise<any>((resolve) => {
+        listener = program.addEventListener("proposalCreated", (data) => resolve(data));
+      });
+
+      const proposalId = await nextProposalId(wallet);
+      await createProposal(wallet, walletAuthority, { admin: {} });
+      const created = await event;
+      await program.removeEventListener(listener);
+
+      expect(created.wallet.toString()).to.equal(wallet.toString());
+      expect(created.proposalId.toString()).to.equal(proposalId.toString());
+      expect(created.proposer.toString()).to.equal(walletAuthority.publicKey.toString());
+      expect(created.category).to.deep.equal({ admin: {} });
+      expect(created.timestamp.toNumber()).to.be.greaterThan(0);
+    });
+  });
+
+  describe("Proposal Expiry", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should fail to expire a proposal before its expiration", async () => {
+      const proposal = await createProposal(wallet, signer1);
+
+      try {
+        await expireProposal(wallet, proposal);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalNotExpired/);
+      }
+    });
+
+    it("Should mark a proposal expired once its expiration passes", async () => {
+      const proposal = await createProposal(wallet, signer1, { regular: {} }, [], 2);
+      await sleep(4);
+
+      await expireProposal(wallet, proposal);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ expired: {} });
+    });
+
+    it("Should fail to create a proposal expiring beyond the wallet's proposal timeout", async () => {
+      try {
+        await createProposal(wallet, signer1, { regular: {} }, [], 7200);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ExpirationExceedsTimeout/);
+      }
+    });
+  });
+
+  describe("Delegated Voting", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+    let delegate: Keypair;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      delegate = Keypair.generate();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey],
+        3
+      );
+      await delegateVote(wallet, signer1, delegate.publicKey);
+    });
+
+    it("Should count a delegate's approval as the delegating member's vote", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await approveProposal(wallet, proposal, delegate);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(1);
+      expect(proposalAccount.approvals[0].signer.toString()).to.equal(signer1.publicKey.toString());
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should block the member from approving after their delegate did", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, delegate);
+
+      try {
+        await approveProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/DelegatedApprovalConflict/);
+      }
+    });
+
+    it("Should block the delegate from approving after the member did", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      try {
+        await approveProposal(wallet, proposal, delegate);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/DelegatedApprovalConflict/);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(1);
+    });
+
+    it("Should fail to delegate to the delegating member", async () => {
+      try {
+        await delegateVote(wallet, signer2, signer2.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/SelfDelegation/);
+      }
+    });
+
+    it("Should fail to delegate to another signer", async () => {
+      try {
+        await delegateVote(wallet, signer2, signer3.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/DelegateIsSigner/);
+      }
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      const member = walletConfigAccount.members.find((m) => m.address.toString() === signer2.publicKey.toString());
+      expect(member?.delegate).to.be.null;
+    });
+  });
+
+  describe("Role Permissions", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should make the authority an admin member", async () => {
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      const authorityMember = walletConfigAccount.members.find(
+        (m) => m.address.toString() === walletAuthority.publicKey.toString()
+      );
+      const plainMember = walletConfigAccount.members.find(
+        (m) => m.address.toString() === signer1.publicKey.toString()
+      );
+      expect(authorityMember?.role).to.deep.equal({ admin: {} });
+      expect(plainMember?.role).to.deep.equal({ member: {} });
+    });
+
+    it("Should let an admin open an Admin proposal", async () => {
+      const proposal = await createProposal(wallet, walletAuthority, { admin: {} });
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.category).to.deep.equal({ admin: {} });
+    });
+
+    it("Should fail to open an Admin proposal as a plain member", async () => {
+      try {
+        await createProposal(wallet, signer1, { admin: {} });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized|not authorized|unauthorized/i);
+      }
+    });
+
+    it("Should fail to open an Emergency proposal as a plain member", async () => {
+      try {
+        await createProposal(wallet, signer1, { emergency: {} });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized|not authorized|unauthorized/i);
+      }
+    });
+  });
+
+  describe("Signer Updates", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+    let newSigners: PublicKey[];
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+      newSigners = [walletAuthority.publicKey, signer1.publicKey, signer3.publicKey];
+    });
+
+    it("Should not apply a signer change approved only by the authority", async () => {
+      await updateSigners(wallet, newSigners, 3, walletAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.signers.map((k) => k.toString())).to.include(signer2.publicKey.toString());
+      expect(walletConfigAccount.threshold).to.equal(2);
+      expect(walletConfigAccount.pendingSignerChange.approvals.length).to.equal(1);
+    });
+
+    it("Should fail to approve the same signer change twice", async () => {
+      try {
+        await updateSigners(wallet, newSigners, 3, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/AlreadyApproved/);
+      }
+    });
+
+    it("Should apply the signer change once every signer approves", async () => {
+      await updateSigners(wallet, newSigners, 3, signer1);
+      await updateSigners(wallet, newSigners, 3, signer2);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.signers.map((k) => k.toString())).to.deep.equal(newSigners.map((k) => k.toString()));
+      expect(walletConfigAccount.threshold).to.equal(3);
+      expect(walletConfigAccount.pendingSignerChange).to.be.null;
+    });
+
+    it("Should keep retained members' roles and drop removed members", async () => {
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      const members = walletConfigAccount.members;
+      expect(members.map((m) => m.address.toString())).to.deep.equal(newSigners.map((k) => k.toString()));
+      expect(members[0].role).to.deep.equal({ admin: {} });
+      expect(members[2].role).to.deep.equal({ member: {} });
+      expect(members.map((m) => m.address.toString())).to.not.include(signer2.publicKey.toString());
+      expect(walletConfigAccount.signerSetVersion.toNumber()).to.equal(1);
+    });
+
+    it("Should fail to replace signers with duplicates", async () => {
+      try {
+        await updateSigners(wallet, [walletAuthority.publicKey, signer1.publicKey, signer1.publicKey], 2, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/DuplicateSigner/);
+      }
+    });
+
+    it("Should fail to replace signers with an unreachable threshold", async () => {
+      try {
+        await updateSigners(wallet, [walletAuthority.publicKey, signer1.publicKey], 3, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ThresholdExceedsSigners/);
+      }
+    });
+  });
+
+  describe("Member Management", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should add a member with a role", async () => {
+      await addMember(wallet, signer3.publicKey, { treasurer: {} }, walletAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      const member = walletConfigAccount.members.find((m) => m.address.toString() === signer3.publicKey.toString());
+      expect(member?.role).to.deep.equal({ treasurer: {} });
+      expect(walletConfigAccount.signers.map((k) => k.toString())).to.include(signer3.publicKey.toString());
+    });
+
+    it("Should fail to add an existing member", async () => {
+      try {
+        await addMember(wallet, signer1.publicKey, { member: {} }, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MemberAlreadyExists/);
+      }
+    });
+
+    it("Should fail to add a member as a non-admin", async () => {
+      try {
+        await addMember(wallet, nonSigner.publicKey, { member: {} }, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized|not authorized|unauthorized/i);
+      }
+    });
+
+    it("Should remove a member and clear delegations to them", async () => {
+      // Signers can't be delegated to, so delegate before the address joins
+      const delegate = Keypair.generate();
+      await delegateVote(wallet, signer1, delegate.publicKey);
+      await addMember(wallet, delegate.publicKey, { member: {} }, walletAuthority);
+
+      await removeMember(wallet, delegate.publicKey, walletAuthority);
+      await removeMember(wallet, signer3.publicKey, walletAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.members.map((m) => m.address.toString())).to.not.include(signer3.publicKey.toString());
+      expect(walletConfigAccount.signers.map((k) => k.toString())).to.not.include(signer3.publicKey.toString());
+      expect(walletConfigAccount.signers.map((k) => k.toString())).to.not.include(delegate.publicKey.toString());
+      const delegator = walletConfigAccount.members.find((m) => m.address.toString() === signer1.publicKey.toString());
+      expect(delegator?.delegate).to.be.null;
+    });
+
+    it("Should fail to remove a member who is not in the wallet", async () => {
+      try {
+        await removeMember(wallet, signer3.publicKey, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MemberNotFound/);
+      }
+    });
+
+    it("Should fail to remove a member when too few signers would remain", async () => {
+      await removeMember(wallet, signer2.publicKey, walletAuthority);
+
+      try {
+        await removeMember(wallet, signer1.publicKey, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InsufficientSigners/);
+      }
+    });
+  });
+
+  describe("Token Transfers", () => {
+    // Create a wallet whose associated token account holds `balance` tokens
+    const setupTokenWallet = async (balance: number) => {
+      const walletAuthority = await fundedKeypair();
+      const recipient = Keypair.generate();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+
+      const mint = await createMint(provider.connection, walletAuthority, walletAuthority.publicKey, null, 6);
+      const source = await getOrCreateAssociatedTokenAccount(provider.connection, walletAuthority, mint, wallet, true);
+      const destination = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        walletAuthority,
+        mint,
+        recipient.publicKey
+      );
+      await mintTo(provider.connection, walletAuthority, mint, source.address, walletAuthority, balance);
+      return { walletAuthority, wallet, mint, source: source.address, destination: destination.address };
+    };
+
+    const proposeAndExecute = async (
+      { walletAuthority, wallet, mint, source, destination }: Awaited<ReturnType<typeof setupTokenWallet>>,
+      amount: number,
+      maxAmount: number
+    ) => {
+      const category = {
+        tokenTransfer: { source, destination, mint, amount: new BN(amount), maxAmount: new BN(maxAmount) },
+      };
+      const proposal = await createProposal(wallet, signer1, category);
+      await approveProposal(wallet, proposal, walletAuthority);
+      await executeProposal(wallet, proposal, signer1, [
+        { pubkey: source, isSigner: false, isWritable: true },
+        { pubkey: destination, isSigner: false, isWritable: true },
+        { pubkey: mint, isSigner: false, isWritable: false },
+      ]);
+    };
+
+    it("Should transfer tokens from the wallet's token account after approval", async () => {
+      const setup = await setupTokenWallet(1000);
+
+      await proposeAndExecute(setup, 400, 400);
+
+      expect(Number((await getAccount(provider.connection, setup.source)).amount)).to.equal(600);
+      expect(Number((await getAccount(provider.connection, setup.destination)).amount)).to.equal(400);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(setup.wallet);
+      expect(walletConfigAccount.spendingUsed.toNumber()).to.equal(400);
+    });
+
+    it("Should clamp the transfer to the available balance", async () => {
+      const setup = await setupTokenWallet(300);
+
+      await proposeAndExecute(setup, 400, 500);
+
+      expect(Number((await getAccount(provider.connection, setup.source)).amount)).to.equal(0);
+      expect(Number((await getAccount(provider.connection, setup.destination)).amount)).to.equal(300);
+    });
+
+    it("Should refuse a proposal whose requested amount exceeds the maximum", async () => {
+      const setup = await setupTokenWallet(1000);
+      const proposalId = await nextProposalId(setup.wallet);
+
+      try {
+        await proposeAndExecute(setup, 400, 300);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/AmountExceedsMax/);
+      }
+      // Rejected at creation, before it could be approved or executed
+      expect(await program.account.proposal.fetchNullable(findProposal(setup.wallet, proposalId))).to.be.null;
+      expect(Number((await getAccount(provider.connection, setup.source)).amount)).to.equal(1000);
+    });
+
+    it("Should fail when the source token account is not owned by the wallet", async () => {
+      const setup = await setupTokenWallet(1000);
+      const authorityAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        setup.walletAuthority,
+        setup.mint,
+        setup.walletAuthority.publicKey
+      );
+      await mintTo(provider.connection, setup.walletAuthority, setup.mint, authorityAccount.address, setup.walletAuthority, 1000);
+
+      try {
+        await proposeAndExecute({ ...setup, source: authorityAccount.address }, 400, 400);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidTokenOwner/);
+      }
+      expect(Number((await getAccount(provider.connection, authorityAccount.address)).amount)).to.equal(1000);
+    });
+
+    it("Should fail when the destination holds a different mint", async () => {
+      const setup = await setupTokenWallet(1000);
+      const otherMint = await createMint(provider.connection, setup.walletAuthority, setup.walletAuthority.publicKey, null, 6);
+      const otherDestination = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        setup.walletAuthority,
+        otherMint,
+        Keypair.generate().publicKey
+      );
+
+      try {
+        await proposeAndExecute({ ...setup, destination: otherDestination.address }, 400, 400);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MintMismatch/);
+      }
+      expect(Number((await getAccount(provider.connection, setup.source)).amount)).to.equal(1000);
+    });
+
+    it("Should fail when the proposal names a different mint than the source holds", async () => {
+      const setup = await setupTokenWallet(1000);
+      const otherMint = await createMint(provider.connection, setup.walletAuthority, setup.walletAuthority.publicKey, null, 6);
+
+      try {
+        await proposeAndExecute({ ...setup, mint: otherMint }, 400, 400);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MintMismatch/);
+      }
+      expect(Number((await getAccount(provider.connection, setup.source)).amount)).to.equal(1000);
+    });
+  });
+
+  describe("Delegate Revocation", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const revokeDelegate = async (delegator: Keypair) => {
+      await program.methods
+        .revokeDelegate()
+        .accounts({
+          walletConfig: wallet,
+          delegator: delegator.publicKey,
+        })
+        .signers([delegator])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should clear a member's delegate", async () => {
+      await delegateVote(wallet, signer1, nonSigner.publicKey);
+      await revokeDelegate(signer1);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      const member = walletConfigAccount.members.find((m) => m.address.toString() === signer1.publicKey.toString());
+      expect(member?.delegate).to.be.null;
+    });
+
+    it("Should fail to revoke for a non-member", async () => {
+      try {
+        await revokeDelegate(nonSigner);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MemberNotFound/);
+      }
+    });
+  });
+
+  describe("Member Status", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const memberStatus = (address: PublicKey) =>
+      program.methods.memberStatus(address).accounts({ walletConfig: wallet }).view();
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should report a plain signer voting for itself", async () => {
+      const status = await memberStatus(walletAuthority.publicKey);
+
+      expect(status.isSigner).to.be.true;
+      expect(status.role).to.deep.equal({ admin: {} });
+      expect(status.isActive).to.be.true;
+      expect(status.delegate).to.be.null;
+      expect(status.effectiveVoter.toString()).to.equal(walletAuthority.publicKey.toString());
+    });
+
+    it("Should report a delegated member voting through its delegate", async () => {
+      await delegateVote(wallet, signer1, nonSigner.publicKey);
+
+      const status = await memberStatus(signer1.publicKey);
+
+      expect(status.isSigner).to.be.true;
+      expect(status.role).to.deep.equal({ member: {} });
+      expect(status.delegate.toString()).to.equal(nonSigner.publicKey.toString());
+      expect(status.effectiveVoter.toString()).to.equal(nonSigner.publicKey.toString());
+    });
+
+    it("Should report that a non-member cannot vote", async () => {
+      const status = await memberStatus(nonSigner.publicKey);
+
+      expect(status.isSigner).to.be.false;
+      expect(status.role).to.be.null;
+      expect(status.isActive).to.be.false;
+      expect(status.effectiveVoter).to.be.null;
+    });
+  });
+
+  describe("Pause and Resume", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const setPaused = async (paused: boolean, admin: Keypair) => {
+      const method = paused ? program.methods.pauseWallet() : program.methods.resumeWallet();
+      await method
+        .accounts({
+          walletConfig: wallet,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should fail to pause as a non-admin", async () => {
+      try {
+        await setPaused(true, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized|not authorized|unauthorized/i);
+      }
+    });
+
+    it("Should block new proposals while paused", async () => {
+      await setPaused(true, walletAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.isActive).to.be.false;
+
+      try {
+        await createProposal(wallet, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ConstraintRaw|WalletInactive|constraint/i);
+      }
+    });
+
+    it("Should allow proposals again after resuming", async () => {
+      await setPaused(false, walletAuthority);
+
+      const proposal = await createProposal(wallet, signer1);
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should fail to resume an active wallet", async () => {
+      try {
+        await setPaused(false, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/WalletAlreadyActive/);
+      }
+    });
+  });
+
+  describe("Emergency Override Execution", () => {
+    it("Should move wallet funds and record the override", async () => {
+      const walletAuthority = await fundedKeypair();
+      const recipient = Keypair.generate();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 2);
+
+      const mint = await createMint(provider.connection, walletAuthority, walletAuthority.publicKey, null, 0);
+      const source = await getOrCreateAssociatedTokenAccount(provider.connection, walletAuthority, mint, wallet, true);
+      const destination = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        walletAuthority,
+        mint,
+        recipient.publicKey
+      );
+      await mintTo(provider.connection, walletAuthority, mint, source.address, walletAuthority, 100);
+
+      const instructions = [toInstructionData(createTransferInstruction(source.address, destination.address, wallet, 25))];
+
+      await program.methods
+        .emergencyOverride(instructions)
+        .accounts({
+          walletConfig: wallet,
+          emergencyAuthority: walletAuthority.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: source.address, isSigner: false, isWritable: true },
+          { pubkey: destination.address, isSigner: false, isWritable: true },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        ])
+        .signers([walletAuthority])
+        .rpc();
+
+      expect(Number((await getAccount(provider.connection, destination.address)).amount)).to.equal(25);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.emergencyLogs.length).to.equal(1);
+      expect(walletConfigAccount.emergencyLogs[0].authority.toString()).to.equal(walletAuthority.publicKey.toString());
+      expect(walletConfigAccount.emergencyLogs[0].instructionCount).to.equal(1);
+    });
+  });
+
+  describe("Executability Check", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const isExecutable = (proposal: PublicKey): Promise<boolean> =>
+      program.methods.isExecutable().accounts({ walletConfig: wallet, proposal }).view();
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should report an approved proposal as executable", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      expect(await isExecutable(proposal)).to.equal(true);
+    });
+
+    it("Should report a pending proposal as not executable", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      expect(await isExecutable(proposal)).to.equal(false);
+    });
+
+    it("Should report an expired proposal as not executable", async () => {
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [], 2);
+      await approveProposal(wallet, proposal, signer1);
+      await sleep(4);
+
+      expect(await isExecutable(proposal)).to.equal(false);
+    });
+
+    it("Should report a proposal short of approvals after a signer's removal as not executable", async () => {
+      const otherAuthority = await fundedKeypair();
+      const otherWallet = await initializeWallet(
+        otherAuthority,
+        [otherAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+      await setPolicy(otherWallet, otherAuthority, { invalidateRemovedApprovals: true });
+      const proposal = await createProposal(otherWallet, signer1);
+      await approveProposal(otherWallet, proposal, signer1);
+      await approveProposal(otherWallet, proposal, signer2);
+
+      await removeMember(otherWallet, signer2.publicKey, otherAuthority);
+
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ approved: {} });
+      const executable = await program.methods
+        .isExecutable()
+        .accounts({ walletConfig: otherWallet, proposal })
+        .view();
+      expect(executable).to.equal(false);
+    });
+  });
+
+  describe("Proposal Descriptions", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should fail to create a proposal with an empty description", async () => {
+      try {
+        await createProposal(wallet, signer1, { regular: {} }, [], 1800, "");
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/EmptyDescription/);
+      }
+    });
+
+    it("Should fail to create a proposal with an oversized description", async () => {
+      try {
+        await createProposal(wallet, signer1, { regular: {} }, [], 1800, "x".repeat(51));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/DescriptionTooLong/);
+      }
+    });
+
+    it("Should accept a description at the maximum length", async () => {
+      const description = "x".repeat(50);
+      const proposal = await createProposal(wallet, signer1, { regular: {} }, [], 1800, description);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.description).to.equal(description);
+    });
+  });
+
+  describe("Weighted Voting", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const setMemberWeight = async (address: PublicKey, weight: number) => {
+      await program.methods
+        .setMemberWeight(address, new BN(weight))
+        .accounts({
+          walletConfig: wallet,
+          admin: walletAuthority.publicKey,
+        })
+        .signers([walletAuthority])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey],
+        2
+      );
+      await setMemberWeight(signer1.publicKey, 10);
+
+      await program.methods
+        .setVotingMode({ weighted: {} }, new BN(10), 0)
+        .accounts({
+          walletConfig: wallet,
+          authority: walletAuthority.publicKey,
+        })
+        .signers([walletAuthority])
+        .rpc();
+    });
+
+    it("Should approve with a single high-weight member", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await approveProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should not approve with several low-weight members", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer2);
+      await approveProposal(wallet, proposal, signer3);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should reject once the high-weight member rejects", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await rejectProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ rejected: {} });
+    });
+
+    it("Should fail to set a weight threshold above the total weight", async () => {
+      try {
+        await program.methods
+          .setVotingMode({ weighted: {} }, new BN(14), 0)
+          .accounts({
+            walletConfig: wallet,
+            authority: walletAuthority.publicKey,
+          })
+          .signers([walletAuthority])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidThreshold/);
+      }
+    });
+  });
+
+  describe("Proportional Voting", () => {
+    // Approvals needed for 6000 bps, keyed by signer count: ceil(n * 0.6)
+    const expectedRequired = { 3: 2, 4: 3, 5: 3 };
+
+    for (const [count, required] of Object.entries(expectedRequired)) {
+      it(`Should need ${required} approvals from ${count} signers at 6000 bps`, async () => {
+        const walletAuthority = await fundedKeypair();
+        const extraSigner = await fundedKeypair();
+        const approvers = [walletAuthority, signer1, signer2, signer3, extraSigner].slice(0, Number(count));
+        const wallet = await initializeWallet(
+          walletAuthority,
+          approvers.map((approver) => approver.publicKey),
+          1
+        );
+        await program.methods
+          .setVotingMode({ proportional: {} }, new BN(0), 6000)
+          .accounts({
+            walletConfig: wallet,
+            authority: walletAuthority.publicKey,
+          })
+          .signers([walletAuthority])
+          .rpc();
+
+        const proposal = await createProposal(wallet, walletAuthority);
+        for (const approver of approvers.slice(0, required - 1)) {
+          await approveProposal(wallet, proposal, approver);
+        }
+        expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ pending: {} });
+
+        await approveProposal(wallet, proposal, approvers[required - 1]);
+        expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ approved: {} });
+      });
+    }
+
+    it("Should fail with a threshold above 10000 bps", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+
+      try {
+        await program.methods
+          .setVotingMode({ proportional: {} }, new BN(0), 10001)
+          .accounts({
+            walletConfig: wallet,
+            authority: walletAuthority.publicKey,
+          })
+          .signers([walletAuthority])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidThreshold/);
+      }
+    });
+  });
+
+  describe("Independent Emergency Approval", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+      await setPolicy(wallet, walletAuthority, { independentEmergencyApproval: true });
+    });
+
+    it("Should not count the proposer's approval toward an emergency proposal", async () => {
+      const proposal = await createProposal(wallet, walletAuthority, { emergency: {} });
+
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(1);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+
+      await approveProposal(wallet, proposal, signer1);
+
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should still count the proposer's approval on regular proposals", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+  });
+
+  describe("Batch Approval", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should approve several proposals at once, skipping expired ones", async () => {
+      const expired = await createProposal(wallet, walletAuthority, { regular: {} }, [], 2);
+      const first = await createProposal(wallet, walletAuthority);
+      const second = await createProposal(wallet, walletAuthority);
+      await sleep(4);
+
+      const tx = await program.methods
+        .approveProposalsBatch()
+        .accounts({
+          walletConfig: wallet,
+          approver: signer1.publicKey,
+        })
+        .remainingAccounts(
+          [expired, first, second].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+        )
+        .signers([signer1])
+        .rpc({ commitment: "confirmed" });
+
+      // The approved proposal ids are returned as a borsh Vec<u64>
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const returnData = Buffer.from(txInfo.meta.returnData.data[0], "base64");
+      const approvedIds = [...Array(returnData.readUInt32LE(0)).keys()].map((i) =>
+        new BN(returnData.subarray(4 + i * 8, 12 + i * 8), "le").toNumber()
+      );
+      const firstAccount = await program.account.proposal.fetch(first);
+      const secondAccount = await program.account.proposal.fetch(second);
+      expect(approvedIds).to.deep.equal([firstAccount.id.toNumber(), secondAccount.id.toNumber()]);
+
+      expect(firstAccount.approvals.map((approval) => approval.signer.toString())).to.deep.equal([signer1.publicKey.toString()]);
+      expect(secondAccount.approvals.map((approval) => approval.signer.toString())).to.deep.equal([signer1.publicKey.toString()]);
+      const expiredAccount = await program.account.proposal.fetch(expired);
+      expect(expiredAccount.approvals.length).to.equal(0);
+    });
+
+    it("Should skip proposals the approver has already approved", async () => {
+      const approved = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, approved, signer1);
+      const fresh = await createProposal(wallet, walletAuthority);
+
+      // The fresh proposal is listed twice; its second entry is skipped too
+      await program.methods
+        .approveProposalsBatch()
+        .accounts({
+          walletConfig: wallet,
+          approver: signer1.publicKey,
+        })
+        .remainingAccounts(
+          [approved, fresh, fresh].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+        )
+        .signers([signer1])
+        .rpc();
+
+      const approvedAccount = await program.account.proposal.fetch(approved);
+      const freshAccount = await program.account.proposal.fetch(fresh);
+      expect(approvedAccount.approvals.length).to.equal(1);
+      expect(freshAccount.approvals.map((approval) => approval.signer.toString())).to.deep.equal([signer1.publicKey.toString()]);
+    });
+  });
+
+  describe("Approval History", () => {
+    it("Should count each approval a member makes", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+      const approvalsMade = async (address: PublicKey): Promise<number> => {
+        const { members } = await program.account.walletConfig.fetch(wallet);
+        return members.find((member) => member.address.equals(address)).approvalsMade.toNumber();
+      };
+
+      await approveProposal(wallet, await createProposal(wallet, walletAuthority), signer1);
+      expect(await approvalsMade(signer1.publicKey)).to.equal(1);
+
+      await approveProposal(wallet, await createProposal(wallet, walletAuthority), signer1);
+      expect(await approvalsMade(signer1.publicKey)).to.equal(2);
+      expect(await approvalsMade(signer2.publicKey)).to.equal(0);
+    });
+  });
+
+  describe("Execution Timelock", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await setPolicy(wallet, walletAuthority, { executionDelay: new BN(3) });
+    });
+
+    it("Should record when a proposal is approved", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await approveProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvedAt).to.not.be.null;
+    });
+
+    it("Should fail to execute before the execution delay elapses", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      try {
+        await executeProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/TimelockNotElapsed/);
+      }
+    });
+
+    it("Should execute once the execution delay has elapsed", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await sleep(5);
+
+      await executeProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+  });
+
+  describe("Approval Expiry", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const reconfirmApproval = async (proposal: PublicKey, approver: Keypair) => {
+      await program.methods
+        .reconfirmApproval()
+        .accounts({
+          walletConfig: wallet,
+          proposal,
+          approver: approver.publicKey,
+        })
+        .signers([approver])
+        .rpc();
+    };
+
+    const compactApprovals = async (proposal: PublicKey) => {
+      await program.methods
+        .compactApprovals()
+        .accounts({
+          walletConfig: wallet,
+          proposal,
+        })
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await setPolicy(wallet, walletAuthority, { approvalValidity: new BN(2) });
+    });
+
+    it("Should record when each approval was made", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await approveProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals[0].signer.toString()).to.equal(signer1.publicKey.toString());
+      expect(proposalAccount.approvals[0].approvedAt.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("Should execute with a recent approval", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      await executeProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should not count a stale approval at execution", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await sleep(4);
+
+      try {
+        await executeProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ApprovalsStale/);
+      }
+    });
+
+    it("Should execute after a stale approval is reconfirmed", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await sleep(4);
+
+      await reconfirmApproval(proposal, signer1);
+      await executeProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should fail to reconfirm without an earlier approval", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      try {
+        await reconfirmApproval(proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ApprovalNotFound/);
+      }
+    });
+
+    it("Should only let current, active signers reconfirm", async () => {
+      const otherAuthority = await fundedKeypair();
+      const otherWallet = await initializeWallet(
+        otherAuthority,
+        [otherAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey],
+        2
+      );
+      const proposal = await createProposal(otherWallet, otherAuthority);
+      await approveProposal(otherWallet, proposal, signer1);
+      await approveProposal(otherWallet, proposal, signer2);
+      const reconfirm = (approver: Keypair) =>
+        program.methods
+          .reconfirmApproval()
+          .accounts({ walletConfig: otherWallet, proposal, approver: approver.publicKey })
+          .signers([approver])
+          .rpc();
+
+      await program.methods
+        .setMemberActive(signer2.publicKey, false)
+        .accounts({ walletConfig: otherWallet, admin: otherAuthority.publicKey })
+        .signers([otherAuthority])
+        .rpc();
+      try {
+        await reconfirm(signer2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MemberInactive/);
+      }
+
+      await removeMember(otherWallet, signer1.publicKey, otherAuthority);
+      try {
+        await reconfirm(signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized/);
+      }
+    });
+
+    it("Should keep fresh approvals when compacting", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      await compactApprovals(proposal);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(1);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should prune stale approvals and return the proposal to pending", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await sleep(4);
+
+      await compactApprovals(proposal);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(0);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+      expect(proposalAccount.approvedAt).to.be.null;
+    });
+
+    it("Should return the proposal to pending when a required approver's approval is pruned", async () => {
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [], 1800, "Sign-off", [], [
+        signer1.publicKey,
+      ]);
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await sleep(4);
+      await reconfirmApproval(proposal, walletAuthority);
+
+      await compactApprovals(proposal);
+
+      // The remaining approval still meets the threshold, but not the
+      // required approver, who can now approve again
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(1);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+
+      await approveProposal(wallet, proposal, signer1);
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should refuse to compact a partially executed proposal", async () => {
+      const instructions = [1, 2].map((n) => ({
+        programId: MEMO_PROGRAM_ID,
+        accounts: [{ pubkey: wallet, isSigner: true, isWritable: false }],
+        data: Buffer.from(`memo ${n}`),
+      }));
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, instructions);
+      await approveProposal(wallet, proposal, signer1);
+      await executeProposal(wallet, proposal, signer1, [{ pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false }], 1);
+      await sleep(4);
+
+      try {
+        await compactApprovals(proposal);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ExecutionInProgress/);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(1);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+      expect(proposalAccount.executedInstructionCount).to.equal(1);
+    });
+  });
+
+  describe("Proposal Editing", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const editProposal = async (proposal: PublicKey, proposer: Keypair, instructions: any[], description: string) => {
+      await program.methods
+        .editProposal(instructions, description)
+        .accounts({
+          walletConfig: wallet,
+          proposal,
+          proposer: proposer.publicKey,
+        })
+        .signers([proposer])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should edit a proposal with no approvals", async () => {
+      const proposal = await createProposal(wallet, signer1);
+      const memo = new TransactionInstruction({
+        keys: [],
+        programId: MEMO_PROGRAM_ID,
+        data: Buffer.from("edited"),
+      });
+
+      await editProposal(proposal, signer1, [toInstructionData(memo)], "Edited proposal");
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.description).to.equal("Edited proposal");
+      expect(proposalAccount.instructions.length).to.equal(1);
+      expect(proposalAccount.instructions[0].programId.toString()).to.equal(MEMO_PROGRAM_ID.toString());
+    });
+
+    it("Should fail to edit a proposal that has an approval", async () => {
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, signer2);
+
+      try {
+        await editProposal(proposal, signer1, [], "Edited proposal");
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalHasApprovals/);
+      }
+    });
+
+    it("Should fail when a non-proposer edits", async () => {
+      const proposal = await createProposal(wallet, signer1);
+
+      try {
+        await editProposal(proposal, signer2, [], "Edited proposal");
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized/);
+      }
+    });
+
+    it("Should clear approvals when editing an approved proposal", async () => {
+      await setPolicy(wallet, walletAuthority, { allowEditsAfterApproval: true });
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, signer1);
+      await approveProposal(wallet, proposal, signer2);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ approved: {} });
+
+      let listener: number;
+      const event = new Promise<any>((resolve) => {
+        listener = program.addEventListener("proposalReset", (data) => resolve(data));
+      });
+      await editProposal(proposal, signer1, [], "Edited proposal");
+      const reset = await event;
+      await program.removeEventListener(listener);
+
+      expect(reset.proposer.toString()).to.equal(signer1.publicKey.toString());
+      expect(reset.clearedApprovals).to.equal(2);
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(0);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+      expect(proposalAccount.approvedAt).to.be.null;
+      expect(proposalAccount.description).to.equal("Edited proposal");
+    });
+  });
+
+  describe("Signer Recovery", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+    let replacement: Keypair;
+
+    const initiateRecovery = async (newSigners: PublicKey[], newThreshold: number, signer: Keypair) => {
+      await program.methods
+        .initiateRecovery(newSigners, newThreshold)
+        .accounts({
+          walletConfig: wallet,
+          signer: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    };
+
+    const completeRecovery = async (signer: Keypair) => {
+      await program.methods
+        .completeRecovery()
+        .accounts({
+          walletConfig: wallet,
+          signer: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      replacement = Keypair.generate();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey],
+        3
+      );
+      await setPolicy(wallet, walletAuthority, { recoveryThreshold: 2, recoveryDelay: new BN(3) });
+    });
+
+    it("Should not complete recovery before the recovery delay", async () => {
+      const newSigners = [signer1.publicKey, signer2.publicKey, replacement.publicKey];
+      await initiateRecovery(newSigners, 2, signer1);
+      await initiateRecovery(newSigners, 2, signer2);
+
+      try {
+        await completeRecovery(signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/RecoveryNotElapsed/);
+      }
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.pendingRecovery.approvals.length).to.equal(2);
+    });
+
+    it("Should replace the signer set once the recovery delay elapses", async () => {
+      await sleep(5);
+
+      await completeRecovery(signer1);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.signers.map((key) => key.toString())).to.deep.equal(
+        [signer1.publicKey, signer2.publicKey, replacement.publicKey].map((key) => key.toString())
+      );
+      expect(walletConfigAccount.threshold).to.equal(2);
+      expect(walletConfigAccount.pendingRecovery).to.be.null;
+    });
+  });
+
+  describe("Spending Window", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+    let treasurer: Keypair;
+
+    const resetSpendingWindow = async (caller: Keypair) => {
+      await program.methods
+        .resetSpendingWindow()
+        .accounts({
+          walletConfig: wallet,
+          caller: caller.publicKey,
+        })
+        .signers([caller])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      treasurer = Keypair.generate();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await addMember(wallet, treasurer.publicKey, { treasurer: {} }, walletAuthority);
+      await program.methods
+        .setSpendingLimits(new BN(LAMPORTS_PER_SOL), new BN(3))
+        .accounts({
+          walletConfig: wallet,
+          approver: walletAuthority.publicKey,
+        })
+        .signers([walletAuthority])
+        .rpc();
+    });
+
+    it("Should report the remaining allowance", async () => {
+      const remaining: BN = await program.methods.spendingRemaining().accounts({ walletConfig: wallet }).view();
+
+      expect(remaining.toNumber()).to.equal(LAMPORTS_PER_SOL);
+    });
+
+    it("Should fail to reset mid-period", async () => {
+      try {
+        await resetSpendingWindow(treasurer);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/SpendingPeriodNotElapsed/);
+      }
+    });
+
+    it("Should fail when a non-treasurer resets", async () => {
+      await sleep(4);
+
+      try {
+        await resetSpendingWindow(signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized/);
+      }
+    });
+
+    it("Should let a treasurer reset once the period has elapsed", async () => {
+      const before = await program.account.walletConfig.fetch(wallet);
+
+      await resetSpendingWindow(treasurer);
+
+      const after = await program.account.walletConfig.fetch(wallet);
+      expect(after.spendingUsed.toNumber()).to.equal(0);
+      expect(after.lastSpendingReset.toNumber()).to.be.greaterThan(before.lastSpendingReset.toNumber());
+    });
+  });
+
+  describe("Program Allowlist", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const memoInstruction = () =>
+      toInstructionData(new TransactionInstruction({ keys: [], programId: MEMO_PROGRAM_ID, data: Buffer.from("memo") }));
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1, {
+        allowedPrograms: [SystemProgram.programId, TOKEN_PROGRAM_ID],
+      });
+    });
+
+    it("Should accept instructions for an allowed program", async () => {
+      const transfer = SystemProgram.transfer({
+        fromPubkey: signer1.publicKey,
+        toPubkey: walletAuthority.publicKey,
+        lamports: 1000,
+      });
+
+      const proposal = await createProposal(wallet, signer1, { regular: {} }, [toInstructionData(transfer)]);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.instructions.length).to.equal(1);
+    });
+
+    it("Should reject instructions for a program not on the allowlist", async () => {
+      try {
+        await createProposal(wallet, signer1, { regular: {} }, [memoInstruction()]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProgramNotAllowed/);
+      }
+    });
+
+    it("Should allow any program when the allowlist is empty", async () => {
+      const openAuthority = await fundedKeypair();
+      const openWallet = await initializeWallet(openAuthority, [openAuthority.publicKey, signer1.publicKey], 1);
+
+      const proposal = await createProposal(openWallet, signer1, { regular: {} }, [memoInstruction()]);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.instructions.length).to.equal(1);
+    });
+  });
+
+  describe("Signer Change Proposals", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should need every signer's approval before executing", async () => {
+      const newSigners = [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey];
+      const proposal = await createProposal(wallet, walletAuthority, {
+        signerChange: { signers: newSigners, threshold: 3 },
+      });
+
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+      try {
+        await executeProposal(wallet, proposal, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalNotApproved|ConstraintRaw/);
+      }
+
+      await approveProposal(wallet, proposal, signer2);
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+
+      await executeProposal(wallet, proposal, walletAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.signers.map((key) => key.toString())).to.deep.equal(
+        newSigners.map((key) => key.toString())
+      );
+      expect(walletConfigAccount.threshold).to.equal(3);
+    });
+
+    it("Should reject an invalid signer set when proposed", async () => {
+      try {
+        await createProposal(wallet, walletAuthority, {
+          signerChange: { signers: [signer1.publicKey, signer1.publicKey], threshold: 1 },
+        });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/DuplicateSigner/);
+      }
+    });
+
+    it("Should reject a proposed signer set over the maximum", async () => {
+      const signers = [walletAuthority.publicKey];
+      while (signers.length < 11) {
+        signers.push(Keypair.generate().publicKey);
+      }
+
+      try {
+        await createProposal(wallet, walletAuthority, { signerChange: { signers, threshold: 2 } });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/TooManySigners/);
+      }
+    });
+  });
+
+  describe("Proposal Metadata", () => {
+    it("Should store the metadata hash unchanged", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      const metadataHash = Array.from(createHash("sha256").update("ipfs://proposal-document").digest());
+
+      const proposalId = await nextProposalId(wallet);
+      const proposal = findProposal(wallet, proposalId);
+      await program.methods
+        .addProposal(proposalId, "With metadata", metadataHash, NO_TAG, { regular: {} }, [], new BN(Math.floor(Date.now() / 1000) + 1800), null, null, null, [], [])
+        .accounts({
+          proposal,
+          walletConfig: wallet,
+          proposer: signer1.publicKey,
+          feePayer: signer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer1])
+        .rpc();
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.metadataHash).to.deep.equal(metadataHash);
+    });
+
+    it("Should leave the metadata hash zeroed when none is given", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+
+      const proposal = await createProposal(wallet, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.metadataHash).to.deep.equal(NO_METADATA);
+    });
+  });
+
+  describe("Proposal Tags", () => {
+    // Proposal.tag follows the discriminator, wallet and proposer
+    const TAG_OFFSET = 8 + 32 + 32;
+
+    const encodeTag = (label: string): number[] => {
+      const tag = new Array(16).fill(0);
+      Buffer.from(label).forEach((byte, i) => (tag[i] = byte));
+      return tag;
+    };
+
+    const createTaggedProposal = async (wallet: PublicKey, proposer: Keypair, tag: number[]): Promise<PublicKey> => {
+      const proposalId = await nextProposalId(wallet);
+      const proposal = findProposal(wallet, proposalId);
+      await program.methods
+        .addProposal(proposalId, "Tagged", NO_METADATA, tag, { regular: {} }, [], new BN(Math.floor(Date.now() / 1000) + 1800), null, null, null, [], [])
+        .accounts({
+          proposal,
+          walletConfig: wallet,
+          proposer: proposer.publicKey,
+          feePayer: proposer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([proposer])
+        .rpc();
+      return proposal;
+    };
+
+    it("Should store the proposal tag", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+
+      const proposal = await createTaggedProposal(wallet, signer1, encodeTag("treasury"));
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.tag).to.deep.equal(encodeTag("treasury"));
+    });
+
+    it("Should filter proposals by tag", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      const tag = encodeTag(`grants-${walletAuthority.publicKey.toBase58().slice(0, 8)}`);
+      const grants = await createTaggedProposal(wallet, walletAuthority, tag);
+      await createTaggedProposal(wallet, signer1, encodeTag("ops"));
+
+      const matches = await program.account.proposal.all([
+        { memcmp: { offset: TAG_OFFSET, bytes: anchor.utils.bytes.bs58.encode(Buffer.from(tag)) } },
+      ]);
+
+      expect(matches.map((match) => match.publicKey.toString())).to.deep.equal([grants.toString()]);
+    });
+  });
+
+  describe("Proposal Creation Result", () => {
+    it("Should return the assigned proposal id and address", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await createProposal(wallet, signer1);
+
+      const proposalId = await nextProposalId(wallet);
+      const proposal = findProposal(wallet, proposalId);
+      const tx = await program.methods
+        .addProposal(proposalId, "Returned id", NO_METADATA, NO_TAG, { regular: {} }, [], new BN(Math.floor(Date.now() / 1000) + 1800), null, null, null, [], [])
+        .accounts({
+          proposal,
+          walletConfig: wallet,
+          proposer: signer1.publicKey,
+          feePayer: signer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer1])
+        .rpc({ commitment: "confirmed" });
+
+      // Returned as a borsh NewProposal { id: u64, proposal: Pubkey }
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const returnData = Buffer.from(txInfo.meta.returnData.data[0], "base64");
+      const returnedId = new BN(returnData.subarray(0, 8), "le");
+      const returnedProposal = new PublicKey(returnData.subarray(8, 40));
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(returnedId.toNumber()).to.equal(proposalAccount.id.toNumber());
+      expect(returnedId.toNumber()).to.equal(1);
+      expect(returnedProposal.toString()).to.equal(proposal.toString());
+    });
+  });
+
+  describe("Authority Transfer", () => {
+    let walletAuthority: Keypair;
+    let newAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const transferAuthority = async (nominee: PublicKey, caller: Keypair) => {
+      await program.methods
+        .transferAuthority(nominee)
+        .accounts({
+          walletConfig: wallet,
+          authority: caller.publicKey,
+        })
+        .signers([caller])
+        .rpc();
+    };
+
+    const acceptAuthority = async (caller: Keypair) => {
+      await program.methods
+        .acceptAuthority()
+        .accounts({
+          walletConfig: wallet,
+          newAuthority: caller.publicKey,
+        })
+        .signers([caller])
+        .rpc();
+    };
+
+    const setSpendingLimits = async (caller: Keypair) => {
+      await program.methods
+        .setSpendingLimits(new BN(LAMPORTS_PER_SOL), new BN(86400))
+        .accounts({
+          walletConfig: wallet,
+          approver: caller.publicKey,
+        })
+        .signers([caller])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      newAuthority = Keypair.generate();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should record the nominated authority without transferring yet", async () => {
+      await transferAuthority(newAuthority.publicKey, walletAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.pendingAuthority.toString()).to.equal(newAuthority.publicKey.toString());
+      expect(walletConfigAccount.authority.toString()).to.equal(walletAuthority.publicKey.toString());
+    });
+
+    it("Should fail to nominate while a transfer is pending", async () => {
+      try {
+        await transferAuthority(signer1.publicKey, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/AuthorityTransferPending/);
+      }
+    });
+
+    it("Should fail when someone other than the nominee accepts", async () => {
+      try {
+        await acceptAuthority(signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized/);
+      }
+    });
+
+    it("Should hand over authority once the nominee accepts", async () => {
+      await acceptAuthority(newAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.authority.toString()).to.equal(newAuthority.publicKey.toString());
+      expect(walletConfigAccount.pendingAuthority).to.be.null;
+      // The wallet address is still derived from the original authority
+      expect(findWalletConfig(walletAuthority.publicKey).toString()).to.equal(wallet.toString());
+
+      await setSpendingLimits(newAuthority);
+      try {
+        await setSpendingLimits(walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized/);
+      }
+    });
+  });
+
+  describe("Quorum", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey],
+        2,
+        { quorum: 3 }
+      );
+    });
+
+    it("Should keep a proposal pending when it meets threshold but not quorum", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should approve once participation reaches quorum", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      await rejectProposal(wallet, proposal, signer2);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should fail with a quorum above the number of signers", async () => {
+      try {
+        await initializeWallet(await fundedKeypair(), [signer1.publicKey, signer2.publicKey], 1, { quorum: 3 });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidQuorum/);
+      }
+    });
+  });
+
+  describe("Instruction Account Normalization", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const meta = (pubkey: PublicKey, isSigner: boolean, isWritable: boolean) => ({ pubkey, isSigner, isWritable });
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should merge duplicate account metas", async () => {
+      const instruction = {
+        programId: MEMO_PROGRAM_ID,
+        accounts: [
+          meta(signer1.publicKey, false, false),
+          meta(wallet, true, false),
+          meta(signer1.publicKey, false, true),
+          meta(signer2.publicKey, false, false),
+        ],
+        data: Buffer.from("memo"),
+      };
+
+      const proposal = await createProposal(wallet, signer1, { regular: {} }, [instruction]);
+
+      const { instructions } = await program.account.proposal.fetch(proposal);
+      expect(
+        instructions[0].accounts.map((account) => [account.pubkey.toString(), account.isSigner, account.isWritable])
+      ).to.deep.equal([
+        [signer1.publicKey.toString(), false, true],
+        [wallet.toString(), true, false],
+        [signer2.publicKey.toString(), false, false],
+      ]);
+    });
+
+    it("Should fail with too many distinct accounts", async () => {
+      const instruction = {
+        programId: MEMO_PROGRAM_ID,
+        accounts: [signer1, signer2, signer3, walletAuthority].map((keypair) => meta(keypair.publicKey, false, false)),
+        data: Buffer.from("memo"),
+      };
+
+      try {
+        await createProposal(wallet, signer1, { regular: {} }, [instruction]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/TooManyInstructionAccounts/);
+      }
+    });
+  });
+
+  describe("Instruction Limit", () => {
+    const MAX_INSTRUCTIONS = 8;
+    const MAX_INSTRUCTION_DATA_LEN = 64;
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const memoInstructions = (count: number) =>
+      [...Array(count).keys()].map((i) => ({ programId: MEMO_PROGRAM_ID, accounts: [], data: Buffer.from([i]) }));
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should accept exactly the maximum number of instructions", async () => {
+      const proposal = await createProposal(wallet, signer1, { regular: {} }, memoInstructions(MAX_INSTRUCTIONS));
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.instructions.length).to.equal(MAX_INSTRUCTIONS);
+    });
+
+    it("Should fail with one instruction over the maximum", async () => {
+      try {
+        await createProposal(wallet, signer1, { regular: {} }, memoInstructions(MAX_INSTRUCTIONS + 1));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/TooManyInstructions/);
+      }
+    });
+
+    it("Should accept instruction data of exactly the maximum length", async () => {
+      const instruction = { programId: MEMO_PROGRAM_ID, accounts: [], data: Buffer.alloc(MAX_INSTRUCTION_DATA_LEN, 1) };
+
+      const proposal = await createProposal(wallet, signer1, { regular: {} }, [instruction]);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.instructions[0].data.length).to.equal(MAX_INSTRUCTION_DATA_LEN);
+    });
+
+    it("Should fail cleanly with instruction data one byte over the maximum", async () => {
+      const instructions = [
+        ...memoInstructions(1),
+        { programId: MEMO_PROGRAM_ID, accounts: [], data: Buffer.alloc(MAX_INSTRUCTION_DATA_LEN + 1, 1) },
+      ];
+
+      try {
+        await createProposal(wallet, signer1, { regular: {} }, instructions);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InstructionDataTooLarge/);
+        expect(error.logs.join("\n")).to.include("Instruction 1 has 65 bytes of data; the maximum is 64");
+      }
+    });
+  });
+
+  describe("Guardian Veto", () => {
+    let walletAuthority: Keypair;
+    let guardian: Keypair;
+    let wallet: PublicKey;
+
+    const vetoProposal = async (proposal: PublicKey, caller: Keypair) => {
+      await program.methods
+        .vetoProposal()
+        .accounts({
+          walletConfig: wallet,
+          proposal,
+          guardian: caller.publicKey,
+        })
+        .signers([caller])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      guardian = Keypair.generate();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await program.methods
+        .setGuardian(guardian.publicKey)
+        .accounts({
+          walletConfig: wallet,
+          authority: walletAuthority.publicKey,
+        })
+        .signers([walletAuthority])
+        .rpc();
+    });
+
+    it("Should let the guardian veto an approved proposal", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      await vetoProposal(proposal, guardian);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ rejected: {} });
+    });
+
+    it("Should fail when a non-guardian vetoes", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      try {
+        await vetoProposal(proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/GuardianOnly/);
+      }
+    });
+
+    it("Should refuse to veto a partially executed proposal", async () => {
+      const instructions = [1, 2].map((n) => ({
+        programId: MEMO_PROGRAM_ID,
+        accounts: [{ pubkey: wallet, isSigner: true, isWritable: false }],
+        data: Buffer.from(`memo ${n}`),
+      }));
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, instructions);
+      await approveProposal(wallet, proposal, signer1);
+      const memoProgram = [{ pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false }];
+      await executeProposal(wallet, proposal, signer1, memoProgram, 1);
+
+      try {
+        await vetoProposal(proposal, guardian);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ExecutionInProgress/);
+      }
+
+      await executeProposal(wallet, proposal, signer1, memoProgram, 1);
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+  });
+
+  describe("Proposal Rate Limiting", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await setPolicy(wallet, walletAuthority, { minProposalInterval: new BN(3) });
+    });
+
+    it("Should block a member's rapid second proposal", async () => {
+      await createProposal(wallet, signer1);
+
+      try {
+        await createProposal(wallet, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalRateLimited/);
+      }
+
+      // Other members are limited separately
+      await createProposal(wallet, walletAuthority);
+    });
+
+    it("Should accept proposals spaced by the interval", async () => {
+      await sleep(4);
+
+      await createProposal(wallet, signer1);
+
+      const { members } = await program.account.walletConfig.fetch(wallet);
+      const member = members.find((member) => member.address.equals(signer1.publicKey));
+      expect(member.lastProposalAt.toNumber()).to.be.greaterThan(0);
+    });
+  });
+
+  describe("Abstention", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const abstainProposal = async (proposal: PublicKey, voter: Keypair) => {
+      await program.methods
+        .abstainProposal()
+        .accounts({
+          walletConfig: wallet,
+          proposal,
+          voter: voter.publicKey,
+        })
+        .signers([voter])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey],
+        2,
+        { quorum: 3 }
+      );
+    });
+
+    it("Should count an abstention toward quorum", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      await abstainProposal(proposal, signer2);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.abstentions.map((key) => key.toString())).to.deep.equal([signer2.publicKey.toString()]);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should not count abstentions toward the threshold", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      await abstainProposal(proposal, signer1);
+      await abstainProposal(proposal, signer2);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should not reject a proposal when signers abstain", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await abstainProposal(proposal, signer1);
+      await abstainProposal(proposal, signer2);
+      await abstainProposal(proposal, signer3);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should fail to vote in more than one way", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await abstainProposal(proposal, signer2);
+
+      try {
+        await abstainProposal(proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/AlreadyVoted/);
+      }
+
+      try {
+        await rejectProposal(wallet, proposal, signer2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/AlreadyVoted/);
+      }
+    });
+  });
+
+  describe("Cross-Wallet Protection", () => {
+    let authorityA: Keypair;
+    let walletA: PublicKey;
+    let walletB: PublicKey;
+
+    before(async () => {
+      authorityA = await fundedKeypair();
+      walletA = await initializeWallet(authorityA, [authorityA.publicKey, signer2.publicKey], 1);
+      const authorityB = await fundedKeypair();
+      walletB = await initializeWallet(authorityB, [authorityB.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should fail to approve another wallet's proposal", async () => {
+      const proposal = await createProposal(walletB, signer1);
+
+      try {
+        await approveProposal(walletA, proposal, authorityA);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/WalletMismatch/);
+      }
+    });
+
+    it("Should fail to reject another wallet's proposal", async () => {
+      const proposal = await createProposal(walletB, signer1);
+
+      try {
+        await rejectProposal(walletA, proposal, authorityA);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/WalletMismatch/);
+      }
+    });
+
+    it("Should fail to execute another wallet's proposal", async () => {
+      const proposal = await createProposal(walletB, signer1);
+      await approveProposal(walletB, proposal, signer1);
+
+      try {
+        await executeProposal(walletA, proposal, authorityA);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/WalletMismatch/);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+  });
+
+  describe("Price Conditions", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const oracleAccounts = [{ pubkey: MOCK_ORACLE, isSigner: false, isWritable: false }];
+
+    const createConditionalProposal = async (comparison: any, threshold: number): Promise<PublicKey> => {
+      const proposalId = await nextProposalId(wallet);
+      const proposal = findProposal(wallet, proposalId);
+      const priceCondition = { oracle: MOCK_ORACLE, priceOffset: 0, comparison, threshold: new BN(threshold) };
+      await program.methods
+        .addProposal(proposalId, "Conditional", NO_METADATA, NO_TAG, { regular: {} }, [], new BN(Math.floor(Date.now() / 1000) + 1800), priceCondition, null, null, [], [])
+        .accounts({
+          proposal,
+          walletConfig: wallet,
+          proposer: signer1.publicKey,
+          feePayer: signer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer1])
+        .rpc();
+      await approveProposal(wallet, proposal, walletAuthority);
+      return proposal;
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should execute when the price condition holds", async () => {
+      const proposal = await createConditionalProposal({ greaterThan: {} }, 100_000_000);
+
+      await executeProposal(wallet, proposal, signer1, oracleAccounts);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should fail to execute when the price condition does not hold", async () => {
+      const proposal = await createConditionalProposal({ greaterThan: {} }, 200_000_000);
+
+      try {
+        await executeProposal(wallet, proposal, signer1, oracleAccounts);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ConditionNotMet/);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should fail to execute without the oracle account", async () => {
+      const proposal = await createConditionalProposal({ lessThan: {} }, 200_000_000);
+
+      try {
+        await executeProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MissingInstructionAccount/);
+      }
+    });
+  });
+
+  describe("Nested Wallets", () => {
+    it("Should count a child wallet's approval in its parent", async () => {
+      const childAuthority = await fundedKeypair();
+      const childWallet = await initializeWallet(childAuthority, [childAuthority.publicKey, signer1.publicKey], 1);
+      const parentAuthority = await fundedKeypair();
+      const parentWallet = await initializeWallet(parentAuthority, [parentAuthority.publicKey, childWallet], 2);
+
+      const parentProposal = await createProposal(parentWallet, parentAuthority);
+      await approveProposal(parentWallet, parentProposal, parentAuthority);
+
+      // The child wallet approves by executing a proposal that invokes approve_proposal
+      const approveInstruction = await program.methods
+        .approveProposal(NO_REASON, 0)
+        .accounts({
+          walletConfig: parentWallet,
+          proposal: parentProposal,
+          approver: childWallet,
+        })
+        .instruction();
+      const childProposal = await createProposal(childWallet, childAuthority, { regular: {} }, [
+        toInstructionData(approveInstruction),
+      ]);
+      await approveProposal(childWallet, childProposal, signer1);
+      await executeProposal(childWallet, childProposal, signer1, [
+        { pubkey: parentWallet, isSigner: false, isWritable: true },
+        { pubkey: parentProposal, isSigner: false, isWritable: true },
+        { pubkey: program.programId, isSigner: false, isWritable: false },
+      ]);
+
+      const proposalAccount = await program.account.proposal.fetch(parentProposal);
+      expect(proposalAccount.approvals.map((approval) => approval.signer.toString())).to.include(childWallet.toString());
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+  });
+
+  describe("Sponsored Proposals", () => {
+    it("Should let a sponsor pay rent for another member's proposal", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      const sponsor = await fundedKeypair();
+      // A proposer with no lamports of its own
+      const proposer = Keypair.generate();
+      await addMember(wallet, proposer.publicKey, { member: {} }, walletAuthority);
+
+      const proposalId = await nextProposalId(wallet);
+      const proposal = findProposal(wallet, proposalId);
+      await program.methods
+        .addProposal(proposalId, "Sponsored", NO_METADATA, NO_TAG, { regular: {} }, [], new BN(Math.floor(Date.now() / 1000) + 1800), null, null, null, [], [])
+        .accounts({
+          proposal,
+          walletConfig: wallet,
+          proposer: proposer.publicKey,
+          feePayer: sponsor.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([proposer, sponsor])
+        .rpc();
+
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.proposer.toString()).to.equal(proposer.publicKey.toString());
+      expect(proposalAccount.feePayer.toString()).to.equal(sponsor.publicKey.toString());
+      expect(await provider.connection.getBalance(proposer.publicKey)).to.equal(0);
+
+      await cancelProposal(wallet, proposal, proposer);
+
+      try {
+        await closeProposal(wallet, proposal, proposer.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ConstraintAddress|address constraint/i);
+      }
+
+      const sponsorBalance = await provider.connection.getBalance(sponsor.publicKey);
+      await closeProposal(wallet, proposal, sponsor.publicKey);
+      expect(await provider.connection.getBalance(sponsor.publicKey)).to.be.greaterThan(sponsorBalance);
+    });
+  });
+
+  describe("Proposal Counters", () => {
+    it("Should track active and terminal proposals across their lifecycle", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      const counters = async () => {
+        const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+        return [
+          walletConfigAccount.activeProposalCount.toNumber(),
+          walletConfigAccount.terminalProposalCount.toNumber(),
+        ];
+      };
+
+      const executed = await createProposal(wallet, signer1);
+      const cancelled = await createProposal(wallet, signer1);
+      const expired = await createProposal(wallet, signer1, { regular: {} }, [], 2);
+      await createProposal(wallet, signer1);
+      expect(await counters()).to.deep.equal([4, 0]);
+
+      // Approval keeps a proposal active
+      await approveProposal(wallet, executed, walletAuthority);
+      expect(await counters()).to.deep.equal([4, 0]);
+
+      await executeProposal(wallet, executed, signer1);
+      await cancelProposal(wallet, cancelled, signer1);
+      await sleep(4);
+      await expireProposal(wallet, expired);
+      expect(await counters()).to.deep.equal([1, 3]);
+
+      await closeProposal(wallet, executed, signer1.publicKey);
+      await closeProposal(wallet, cancelled, signer1.publicKey);
+      expect(await counters()).to.deep.equal([1, 1]);
+    });
+  });
+
+  describe("Proposal Dependencies", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const createDependentProposal = async (dependsOn: BN): Promise<PublicKey> => {
+      const proposalId = await nextProposalId(wallet);
+      const proposal = findProposal(wallet, proposalId);
+      await program.methods
+        .addProposal(proposalId, "Dependent", NO_METADATA, NO_TAG, { regular: {} }, [], new BN(Math.floor(Date.now() / 1000) + 1800), null, dependsOn, null, [], [])
+        .accounts({
+          proposal,
+          walletConfig: wallet,
+          proposer: signer1.publicKey,
+          feePayer: signer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer1])
+        .rpc();
+      return proposal;
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should execute a proposal only after its dependency", async () => {
+      const first = await createProposal(wallet, signer1);
+      const firstId = (await program.account.proposal.fetch(first)).id;
+      const second = await createDependentProposal(firstId);
+      await approveProposal(wallet, first, walletAuthority);
+      await approveProposal(wallet, second, walletAuthority);
+      const dependencyAccounts = [{ pubkey: first, isSigner: false, isWritable: false }];
+
+      try {
+        await executeProposal(wallet, second, signer1, dependencyAccounts);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/DependencyNotExecuted/);
+      }
+
+      await executeProposal(wallet, first, signer1);
+      await executeProposal(wallet, second, signer1, dependencyAccounts);
+
+      const proposalAccount = await program.account.proposal.fetch(second);
+      expect(proposalAccount.dependsOn.toNumber()).to.equal(firstId.toNumber());
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should keep a dependency open until its dependent has run", async () => {
+      const first = await createProposal(wallet, signer1);
+      const firstId = (await program.account.proposal.fetch(first)).id;
+      const second = await createDependentProposal(firstId);
+      await approveProposal(wallet, first, walletAuthority);
+      await approveProposal(wallet, second, walletAuthority);
+      await executeProposal(wallet, first, signer1);
+
+      try {
+        await closeProposal(wallet, first, signer1.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalHasDependents/);
+      }
+
+      await executeProposal(wallet, second, signer1, [{ pubkey: first, isSigner: false, isWritable: false }]);
+      expect((await program.account.proposal.fetch(second)).status).to.deep.equal({ executed: {} });
+
+      await closeProposal(wallet, first, signer1.publicKey);
+      expect(await provider.connection.getAccountInfo(first)).to.be.null;
+    });
+
+    it("Should fail to depend on a proposal that does not exist yet", async () => {
+      try {
+        await createDependentProposal(await nextProposalId(wallet));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidProposalId/);
+      }
+    });
+  });
+
+  describe("Execution Failures", () => {
+    it("Should record a reported execution failure", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1, {
+        spendingLimit: new BN(10).pow(new BN(18)),
+      });
+
+      // A transfer of far more lamports than signer1 holds, which the system
+      // program rejects with custom error 1 (ResultWithNegativeLamports)
+      const transfer = SystemProgram.transfer({
+        fromPubkey: signer1.publicKey,
+        toPubkey: walletAuthority.publicKey,
+        lamports: 1_000_000 * LAMPORTS_PER_SOL,
+      });
+      const proposal = await createProposal(wallet, signer1, { regular: {} }, [toInstructionData(transfer)]);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      try {
+        await executeProposal(wallet, proposal, signer1, [
+          { pubkey: signer1.publicKey, isSigner: true, isWritable: true },
+          { pubkey: walletAuthority.publicKey, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/custom program error: 0x1/);
+      }
+
+      let listener: number;
+      const event = new Promise<any>((resolve) => {
+        listener = program.addEventListener("executionAttempted", (data) => resolve(data));
+      });
+
+      await program.methods
+        .recordExecutionFailure(1)
+        .accounts({
+          walletConfig: wallet,
+          proposal,
+          reporter: signer1.publicKey,
+        })
+        .signers([signer1])
+        .rpc();
+      const attempted = await event;
+      await program.removeEventListener(listener);
+
+      expect(attempted.success).to.be.false;
+      expect(attempted.errorCode).to.equal(1);
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.failedAttempts).to.equal(1);
+      expect(proposalAccount.lastExecutionError).to.equal(1);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+  });
+
+  describe("Signer Set Versioning", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    beforeEach(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should stamp proposals with the signer set version", async () => {
+      await removeMember(wallet, signer2.publicKey, walletAuthority);
+      const proposal = await createProposal(wallet, signer1);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(walletConfigAccount.signerSetVersion.toNumber()).to.equal(1);
+      expect(proposalAccount.signerSetVersion.toNumber()).to.equal(1);
+    });
+
+    it("Should not count approvals from removed signers at execution", async () => {
+      await setPolicy(wallet, walletAuthority, { invalidateRemovedApprovals: true });
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, signer1);
+      await approveProposal(wallet, proposal, signer2);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ approved: {} });
+
+      await removeMember(wallet, signer2.publicKey, walletAuthority);
+
+      try {
+        await executeProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/SignerSetChanged/);
+      }
+    });
+
+    it("Should execute when remaining signers still meet the threshold", async () => {
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, signer1);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      await removeMember(wallet, signer2.publicKey, walletAuthority);
+      await executeProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+  });
+
+  describe("Spending Budgets", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+    let recipient: Keypair;
+
+    const encodeTag = (label: string): number[] => {
+      const tag = new Array(16).fill(0);
+      Buffer.from(label).forEach((byte, i) => (tag[i] = byte));
+      return tag;
+    };
+    const OPS = encodeTag("ops");
+    const GRANTS = encodeTag("grants");
+
+    // A transfer funded by signer1, who must execute so it can sign the inner transfer
+    const createTransferProposal = async (tag: number[], lamports: number): Promise<PublicKey> => {
+      const transfer = SystemProgram.transfer({
+        fromPubkey: signer1.publicKey,
+        toPubkey: recipient.publicKey,
+        lamports,
+      });
+      const proposalId = await nextProposalId(wallet);
+      const proposal = findProposal(wallet, proposalId);
+      await program.methods
+        .addProposal(proposalId, "Budgeted", NO_METADATA, tag, { regular: {} }, [toInstructionData(transfer)], new BN(Math.floor(Date.now() / 1000) + 1800), null, null, null, [], [])
+        .accounts({
+          proposal,
+          walletConfig: wallet,
+          proposer: walletAuthority.publicKey,
+          feePayer: walletAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([walletAuthority])
+        .rpc();
+      return proposal;
+    };
+
+    const transferAccounts = (): AccountMeta[] => [
+      { pubkey: signer1.publicKey, isSigner: true, isWritable: true },
+      { pubkey: recipient.publicKey, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ];
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      recipient = Keypair.generate();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey],
+        1,
+        { spendingLimit: new BN(LAMPORTS_PER_SOL) }
+      );
+      await program.methods
+        .setBudget(OPS, new BN(0.2 * LAMPORTS_PER_SOL))
+        .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+    });
+
+    it("Should block a proposal over its tag's budget despite room in the overall limit", async () => {
+      const proposal = await createTransferProposal(OPS, 0.3 * LAMPORTS_PER_SOL);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      const preview = await program.methods
+        .previewSpending()
+        .accounts({ walletConfig: wallet, proposal })
+        .view();
+      expect(preview.projectedSpendingUsed.toNumber()).to.equal(0.3 * LAMPORTS_PER_SOL);
+      expect(preview.exceedsLimit).to.be.true;
+
+      try {
+        await executeProposal(wallet, proposal, signer1, transferAccounts());
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/BudgetExceeded/);
+      }
+    });
+
+    it("Should charge executed spending to the matching budget only", async () => {
+      const ops = await createTransferProposal(OPS, 0.1 * LAMPORTS_PER_SOL);
+      const grants = await createTransferProposal(GRANTS, 0.5 * LAMPORTS_PER_SOL);
+      await approveProposal(wallet, ops, walletAuthority);
+      await approveProposal(wallet, grants, walletAuthority);
+
+      await executeProposal(wallet, ops, signer1, transferAccounts());
+      await executeProposal(wallet, grants, signer1, transferAccounts());
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.budgets.length).to.equal(1);
+      expect(walletConfigAccount.budgets[0].used.toNumber()).to.equal(0.1 * LAMPORTS_PER_SOL);
+      expect(walletConfigAccount.spendingUsed.toNumber()).to.equal(0.6 * LAMPORTS_PER_SOL);
+    });
+
+    it("Should remove a budget set to zero", async () => {
+      await program.methods
+        .setBudget(OPS, new BN(0))
+        .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.budgets.length).to.equal(0);
+    });
+  });
+
+  describe("Proposal Summaries", () => {
+    it("Should summarize a proposal's status and votes", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, signer1);
+      await rejectProposal(wallet, proposal, signer2);
+
+      const summary = await program.methods
+        .getProposalSummary()
+        .accounts({ proposal })
+        .view();
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(summary.id.toNumber()).to.equal(proposalAccount.id.toNumber());
+      expect(summary.status).to.deep.equal(proposalAccount.status);
+      expect(summary.category).to.deep.equal(proposalAccount.category);
+      expect(summary.approvals).to.equal(1);
+      expect(summary.rejections).to.equal(1);
+      expect(summary.expiration.toNumber()).to.equal(proposalAccount.expiration.toNumber());
+    });
+
+    it("Should report the time until a proposal expires", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      const proposal = await createProposal(wallet, signer1);
+
+      const remaining = await program.methods
+        .timeUntilExpiry()
+        .accounts({ proposal })
+        .view();
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      const slot = await provider.connection.getSlot();
+      const clockTime = await provider.connection.getBlockTime(slot);
+      const expected = proposalAccount.expiration.toNumber() - clockTime;
+      expect(Math.abs(remaining.toNumber() - expected)).to.be.at.most(2);
+    });
+
+    it("Should report negative time for an expired proposal", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      const proposal = await createProposal(wallet, signer1, { regular: {} }, [], 2);
+      await sleep(4);
+
+      const remaining = await program.methods
+        .timeUntilExpiry()
+        .accounts({ proposal })
+        .view();
+
+      expect(remaining.toNumber()).to.be.lessThan(0);
+    });
+  });
+
+  describe("Scheduled Proposals", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const createScheduledProposal = async (notBefore: BN): Promise<PublicKey> => {
+      const proposalId = await nextProposalId(wallet);
+      const proposal = findProposal(wallet, proposalId);
+      await program.methods
+        .addProposal(proposalId, "Scheduled", NO_METADATA, NO_TAG, { regular: {} }, [], new BN(Math.floor(Date.now() / 1000) + 1800), null, null, notBefore, [], [])
+        .accounts({
+          proposal,
+          walletConfig: wallet,
+          proposer: signer1.publicKey,
+          feePayer: signer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer1])
+        .rpc();
+      return proposal;
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should block execution until the scheduled date", async () => {
+      const proposal = await createScheduledProposal(new BN(Math.floor(Date.now() / 1000) + 3));
+      await approveProposal(wallet, proposal, walletAuthority);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ approved: {} });
+
+      try {
+        await executeProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalNotYetActive/);
+      }
+
+      await sleep(4);
+      await executeProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should fail to schedule a proposal after its expiration", async () => {
+      try {
+        await createScheduledProposal(new BN(Math.floor(Date.now() / 1000) + 3600));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidExpiration/);
+      }
+    });
+  });
+
+  describe("Execution Cooldown", () => {
+    it("Should throttle back-to-back executions and allow spaced ones", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await setPolicy(wallet, walletAuthority, { minExecutionInterval: new BN(3) });
+
+      const first = await createProposal(wallet, signer1);
+      const second = await createProposal(wallet, signer1);
+      await approveProposal(wallet, first, walletAuthority);
+      await approveProposal(wallet, second, walletAuthority);
+
+      await executeProposal(wallet, first, signer1);
+      try {
+        await executeProposal(wallet, second, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ExecutionCooldownActive/);
+      }
+
+      await sleep(4);
+      await executeProposal(wallet, second, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(second);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.lastExecutionAt.toNumber()).to.equal(proposalAccount.executedAt.toNumber());
+    });
+  });
+
+  describe("Proposer Auto-Approval", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    beforeEach(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should not approve for the proposer by default", async () => {
+      const proposal = await createProposal(wallet, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(0);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should record the proposer's approval when enabled", async () => {
+      await setPolicy(wallet, walletAuthority, { autoApproveProposer: true });
+
+      const proposal = await createProposal(wallet, signer1);
+
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.map((approval) => approval.signer.toString())).to.deep.equal([
+        signer1.publicKey.toString(),
+      ]);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+
+      await approveProposal(wallet, proposal, signer2);
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should approve immediately when the proposer alone meets the threshold", async () => {
+      const soloAuthority = await fundedKeypair();
+      const soloWallet = await initializeWallet(soloAuthority, [soloAuthority.publicKey, signer1.publicKey], 1);
+      await setPolicy(soloWallet, soloAuthority, { autoApproveProposer: true });
+
+      const proposal = await createProposal(soloWallet, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+      expect(proposalAccount.approvedAt).to.not.be.null;
+    });
+
+    it("Should not approve for a proposer who is not a signer", async () => {
+      await setPolicy(wallet, walletAuthority, { autoApproveProposer: true });
+      const outsider = await fundedKeypair();
+
+      const proposal = await createProposal(wallet, outsider);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(0);
+    });
+  });
+
+  describe("Configuration Snapshots", () => {
+    const exportConfig = (wallet: PublicKey) =>
+      program.methods.exportConfig().accounts({ walletConfig: wallet }).view();
+
+    const importSnapshot = async (authority: Keypair, snapshot: any): Promise<PublicKey> => {
+      const wallet = findWalletConfig(authority.publicKey);
+      await program.methods
+        .initializeWalletFromSnapshot(snapshot)
+        .accounts({
+          walletConfig: wallet,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      return wallet;
+    };
+
+    it("Should recreate an equivalent wallet from an exported snapshot", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2,
+        { allowedPrograms: [MEMO_PROGRAM_ID], quorum: 2 }
+      );
+      await setPolicy(wallet, walletAuthority, { executionDelay: new BN(60), autoApproveProposer: true });
+      await program.methods
+        .setBudget(Array.from(Buffer.concat([Buffer.from("ops"), Buffer.alloc(13)])), new BN(LAMPORTS_PER_SOL))
+        .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+
+      const snapshot = await exportConfig(wallet);
+      expect(snapshot.version).to.equal(11);
+      expect(snapshot.signers.length).to.equal(3);
+      expect(snapshot.members[0].role).to.deep.equal({ admin: {} });
+
+      const newAuthority = await fundedKeypair();
+      const recreated = await importSnapshot(newAuthority, snapshot);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(recreated);
+      expect(walletConfigAccount.authority.toString()).to.equal(newAuthority.publicKey.toString());
+      expect(walletConfigAccount.proposalCount.toNumber()).to.equal(0);
+      expect(JSON.stringify(await exportConfig(recreated))).to.equal(JSON.stringify(snapshot));
+    });
+
+    it("Should reject a snapshot from an unsupported version", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      const snapshot = await exportConfig(wallet);
+
+      try {
+        await importSnapshot(await fundedKeypair(), { ...snapshot, version: snapshot.version + 1 });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/UnsupportedSnapshotVersion/);
+      }
+    });
+
+    it("Should reject a snapshot whose members don't match its signers", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      const snapshot = await exportConfig(wallet);
+
+      try {
+        await importSnapshot(await fundedKeypair(), { ...snapshot, members: snapshot.members.slice(1) });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidSnapshot/);
+      }
+    });
+  });
+
+  describe("Rejection Threshold", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey],
+        2
+      );
+      await setPolicy(wallet, walletAuthority, { rejectionThreshold: 1 });
+    });
+
+    it("Should reject a proposal once the rejection threshold is reached", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      // Three signers could still approve, but one rejection is enough
+      await rejectProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ rejected: {} });
+    });
+
+    it("Should fail with a rejection threshold above the number of signers", async () => {
+      try {
+        await setPolicy(wallet, walletAuthority, { rejectionThreshold: 5 });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidThreshold/);
+      }
+    });
+  });
+
+  describe("Authority Membership", () => {
+    it("Should record an authority that is a signer and count its votes", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.authorityIsSigner).to.be.true;
+
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, walletAuthority);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should record an external authority that cannot vote", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [signer1.publicKey, signer2.publicKey], 1);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.authorityIsSigner).to.be.false;
+
+      const proposal = await createProposal(wallet, signer1);
+      try {
+        await approveProposal(wallet, proposal, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized/);
+      }
+
+      // Privileged operations don't depend on membership
+      await program.methods
+        .setSpendingLimits(new BN(2 * LAMPORTS_PER_SOL), new BN(86400))
+        .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+    });
+
+    it("Should not count a signer authority's votes when the policy excludes them", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        1,
+        { adminThreshold: 2 }
+      );
+      await setPolicy(wallet, walletAuthority, { excludeAuthorityVotes: true });
+
+      const proposal = await createProposal(wallet, signer1);
+      await approveProposal(wallet, proposal, walletAuthority);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ pending: {} });
+
+      await approveProposal(wallet, proposal, signer1);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should fail to exclude the authority's votes when thresholds become unreachable", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 2);
+
+      try {
+        await setPolicy(wallet, walletAuthority, { excludeAuthorityVotes: true });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidThreshold/);
+      }
+    });
+  });
+
+  describe("Timelocked Spending Limits", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const proposeSpendingLimit = async (limit: BN, resetSpending: boolean) => {
+      await program.methods
+        .proposeSpendingLimit(limit, new BN(86400), resetSpending)
+        .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+    };
+
+    const applySpendingLimit = async () => {
+      await program.methods
+        .applySpendingLimit()
+        .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await setPolicy(wallet, walletAuthority, { spendingLimitDelay: new BN(3) });
+    });
+
+    it("Should block immediate spending limit changes while a delay is set", async () => {
+      try {
+        await program.methods
+          .setSpendingLimits(new BN(5 * LAMPORTS_PER_SOL), new BN(86400))
+          .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+          .signers([walletAuthority])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/SpendingLimitChangeTimelocked/);
+      }
+    });
+
+    it("Should apply a proposed spending limit only after the delay", async () => {
+      await proposeSpendingLimit(new BN(5 * LAMPORTS_PER_SOL), false);
+
+      try {
+        await applySpendingLimit();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/SpendingLimitDelayNotElapsed/);
+      }
+
+      const before = await program.account.walletConfig.fetch(wallet);
+      await sleep(4);
+      await applySpendingLimit();
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.spendingLimit.toNumber()).to.equal(5 * LAMPORTS_PER_SOL);
+      expect(walletConfigAccount.pendingSpendingLimit).to.be.null;
+      expect(walletConfigAccount.lastSpendingReset.toNumber()).to.equal(before.lastSpendingReset.toNumber());
+    });
+
+    it("Should fail to apply when no change is pending", async () => {
+      try {
+        await applySpendingLimit();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NoSpendingLimitChangePending/);
+      }
+    });
+  });
+
+  describe("SOL Transfers", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+    const recipient = Keypair.generate();
+
+    const proposeAndExecute = async (lamports: number) => {
+      const category = { solTransfer: { recipient: recipient.publicKey, lamports: new BN(lamports) } };
+      const proposal = await createProposal(wallet, signer1, category);
+      await approveProposal(wallet, proposal, walletAuthority);
+      await executeProposal(wallet, proposal, signer1, [
+        { pubkey: recipient.publicKey, isSigner: false, isWritable: true },
+      ]);
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 2, {
+        spendingLimit: new BN(10 * LAMPORTS_PER_SOL),
+      });
+
+      // Fund the wallet PDA so it holds lamports above its rent-exempt minimum
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({ fromPubkey: walletAuthority.publicKey, toPubkey: wallet, lamports: LAMPORTS_PER_SOL })
+        ),
+        [walletAuthority]
+      );
+    });
+
+    it("Should transfer SOL from the wallet PDA after approval", async () => {
+      const walletBefore = await provider.connection.getBalance(wallet);
+
+      await proposeAndExecute(LAMPORTS_PER_SOL / 2);
+
+      expect(await provider.connection.getBalance(recipient.publicKey)).to.equal(LAMPORTS_PER_SOL / 2);
+      expect(await provider.connection.getBalance(wallet)).to.equal(walletBefore - LAMPORTS_PER_SOL / 2);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.spendingUsed.toNumber()).to.equal(LAMPORTS_PER_SOL / 2);
+    });
+
+    it("Should fail when the transfer would leave the wallet below rent exemption", async () => {
+      const walletBefore = await provider.connection.getBalance(wallet);
+
+      try {
+        await proposeAndExecute(walletBefore);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InsufficientWalletBalance/);
+      }
+      expect(await provider.connection.getBalance(wallet)).to.equal(walletBefore);
+    });
+  });
+
+  describe("Re-entrancy Protection", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should reject a proposal instruction that calls back into the program", async () => {
+      const target = await createProposal(wallet, walletAuthority);
+
+      // The inner instruction approves another proposal as signer1, who signs the execution
+      const reentrantApproval = await program.methods
+        .approveProposal(NO_REASON, 0)
+        .accounts({ walletConfig: wallet, proposal: target, approver: signer1.publicKey })
+        .instruction();
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [
+        toInstructionData(reentrantApproval),
+      ]);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      try {
+        await executeProposal(wallet, proposal, signer1, [
+          { pubkey: target, isSigner: false, isWritable: true },
+          { pubkey: signer1.publicKey, isSigner: true, isWritable: false },
+          { pubkey: program.programId, isSigner: false, isWritable: false },
+        ]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ReentrantCall/);
+      }
+
+      const targetAccount = await program.account.proposal.fetch(target);
+      expect(targetAccount.approvals.length).to.equal(0);
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.executing).to.be.false;
+    });
+  });
+
+  describe("Member Activation", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const setMemberActive = async (address: PublicKey, active: boolean, admin: Keypair = walletAuthority) => {
+      await program.methods
+        .setMemberActive(address, active)
+        .accounts({ walletConfig: wallet, admin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2,
+        { adminThreshold: 2 }
+      );
+    });
+
+    it("Should stop a deactivated member from approving or proposing", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await setMemberActive(signer2.publicKey, false);
+
+      try {
+        await approveProposal(wallet, proposal, signer2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MemberInactive/);
+      }
+
+      try {
+        await createProposal(wallet, signer2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MemberInactive/);
+      }
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      const member = walletConfigAccount.members.find(m => m.address.toString() === signer2.publicKey.toString());
+      expect(member.isActive).to.be.false;
+      expect(member.role).to.deep.equal({ member: {} });
+    });
+
+    it("Should stop a deactivated member from rejecting or abstaining", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      try {
+        await rejectProposal(wallet, proposal, signer2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MemberInactive/);
+      }
+
+      try {
+        await program.methods
+          .abstainProposal()
+          .accounts({ walletConfig: wallet, proposal, voter: signer2.publicKey })
+          .signers([signer2])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MemberInactive/);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.rejections.length).to.equal(0);
+      expect(proposalAccount.abstentions.length).to.equal(0);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+
+    it("Should restore a reactivated member's ability to approve", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      await setMemberActive(signer2.publicKey, true);
+      await approveProposal(wallet, proposal, signer2);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should not deactivate members needed to meet the threshold", async () => {
+      await setMemberActive(signer2.publicKey, false);
+
+      try {
+        await setMemberActive(signer1.publicKey, false);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InsufficientSigners/);
+      }
+
+      await setMemberActive(signer2.publicKey, true);
+    });
+
+    it("Should only let admins change a member's activation", async () => {
+      try {
+        await setMemberActive(signer2.publicKey, false, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized/);
+      }
+    });
+
+    it("Should pass a signer change with every active signer's approval", async () => {
+      await setMemberActive(signer2.publicKey, false);
+      const newSigners = [walletAuthority.publicKey, signer1.publicKey, signer3.publicKey];
+      const proposal = await createProposal(wallet, walletAuthority, {
+        signerChange: { signers: newSigners, threshold: 2 },
+      });
+
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+
+      await executeProposal(wallet, proposal, walletAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.signers.map((key) => key.toString())).to.deep.equal(
+        newSigners.map((key) => key.toString())
+      );
+    });
+  });
+
+  describe("Vote Reasons", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const reasonHash = (text: string) => Array.from(createHash("sha256").update(text).digest());
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should store the reason hash with each vote", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      const approveReason = reasonHash("Budget was reviewed");
+      const rejectReason = reasonHash("Recipient is unverified");
+
+      await approveProposal(wallet, proposal, signer1, approveReason);
+      await rejectProposal(wallet, proposal, signer2, rejectReason);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals[0].reason).to.deep.equal(approveReason);
+      expect(proposalAccount.rejections[0].signer.toString()).to.equal(signer2.publicKey.toString());
+      expect(proposalAccount.rejections[0].reason).to.deep.equal(rejectReason);
+    });
+
+    it("Should accept votes without a reason", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      await approveProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals[0].reason).to.deep.equal(NO_REASON);
+    });
+  });
+
+  describe("Execution Grace Period", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should execute an approved proposal within the grace period after expiration", async () => {
+      await setPolicy(wallet, walletAuthority, { executionGracePeriod: new BN(30) });
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [], 3);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      await sleep(4);
+      await executeProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should still reject approvals after expiration", async () => {
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [], 3);
+
+      await sleep(4);
+      try {
+        await approveProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalExpired/);
+      }
+    });
+
+    it("Should fail to execute once the grace period has passed", async () => {
+      await setPolicy(wallet, walletAuthority, { executionGracePeriod: new BN(1) });
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [], 3);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      await sleep(5);
+      try {
+        await executeProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ProposalExpired/);
+      }
+    });
+  });
+
+  describe("Proposal Status Codes", () => {
+    // Proposal.status_code follows the discriminator, wallet, proposer and tag
+    const STATUS_CODE_OFFSET = 8 + 32 + 32 + 16;
+    const STATUS_CODES = { pending: 0, approved: 1, rejected: 2, executed: 3, expired: 4, cancelled: 5 };
+
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    // The status code byte must match the decoded status enum
+    const expectStatusCode = async (proposal: PublicKey, status: string) => {
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ [status]: {} });
+      const { data } = await provider.connection.getAccountInfo(proposal);
+      expect(data[STATUS_CODE_OFFSET]).to.equal(STATUS_CODES[status]);
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should keep the status code in sync through approval and execution", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await expectStatusCode(proposal, "pending");
+
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await expectStatusCode(proposal, "approved");
+
+      await executeProposal(wallet, proposal, signer1);
+      await expectStatusCode(proposal, "executed");
+    });
+
+    it("Should keep the status code in sync through rejection, cancellation and expiry", async () => {
+      const rejected = await createProposal(wallet, walletAuthority);
+      await rejectProposal(wallet, rejected, signer1);
+      await rejectProposal(wallet, rejected, signer2);
+      await expectStatusCode(rejected, "rejected");
+
+      const cancelled = await createProposal(wallet, walletAuthority);
+      await cancelProposal(wallet, cancelled, walletAuthority);
+      await expectStatusCode(cancelled, "cancelled");
+
+      const expired = await createProposal(wallet, walletAuthority, { regular: {} }, [], 2);
+      await sleep(3);
+      await expireProposal(wallet, expired);
+      await expectStatusCode(expired, "expired");
+    });
+
+    it("Should filter a wallet's proposals by status code", async () => {
+      const pending = await createProposal(wallet, walletAuthority);
+
+      const matches = await program.account.proposal.all([
+        { memcmp: { offset: 8, bytes: wallet.toBase58() } },
+        { memcmp: { offset: STATUS_CODE_OFFSET, bytes: anchor.utils.bytes.bs58.encode([STATUS_CODES.pending]) } },
+      ]);
+
+      expect(matches.map((match) => match.publicKey.toString())).to.deep.equal([pending.toString()]);
+    });
+  });
+
+  describe("Multi-Option Proposals", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const label = (text: string): number[] => {
+      const bytes = new Array(16).fill(0);
+      Buffer.from(text).forEach((byte, i) => (bytes[i] = byte));
+      return bytes;
+    };
+
+    const memo = (text: string) => ({ programId: MEMO_PROGRAM_ID, accounts: [], data: Buffer.from(text) });
+
+    // Option "grants" runs the first memo, option "ops" the other two
+    const createOptionProposal = () =>
+      createProposal(
+        wallet,
+        walletAuthority,
+        { regular: {} },
+        [memo("grants"), memo("ops-1"), memo("ops-2")],
+        1800,
+        "Pick a budget",
+        [
+          { label: label("grants"), instructionCount: 1 },
+          { label: label("ops"), instructionCount: 2 },
+        ]
+      );
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey, signer3.publicKey],
+        2
+      );
+    });
+
+    it("Should execute the first option to reach the threshold", async () => {
+      const proposal = await createOptionProposal();
+
+      await approveProposal(wallet, proposal, signer1, NO_REASON, 1);
+      await approveProposal(wallet, proposal, walletAuthority, NO_REASON, 0);
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+
+      await approveProposal(wallet, proposal, signer2, NO_REASON, 1);
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+      expect(proposalAccount.selectedOption).to.equal(1);
+
+      await executeProposal(wallet, proposal, signer1, [
+        { pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false },
+      ]);
+
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+      expect(proposalAccount.executedInstructionCount).to.equal(2);
+    });
+
+    it("Should reject votes for an option the proposal doesn't have", async () => {
+      const proposal = await createOptionProposal();
+
+      try {
+        await approveProposal(wallet, proposal, signer1, NO_REASON, 2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidProposalOption/);
+      }
+    });
+
+    it("Should fail when the options don't cover the instructions exactly", async () => {
+      try {
+        const options = [
+          { label: label("grants"), instructionCount: 1 },
+          { label: label("ops"), instructionCount: 1 },
+        ];
+        await createProposal(wallet, walletAuthority, { regular: {} }, [memo("grants")], 1800, "Pick a budget", options);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidProposalOptions/);
+      }
+    });
+  });
+
+  describe("Open Proposal Cap", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await setPolicy(wallet, walletAuthority, { maxOpenProposals: 2 });
+    });
+
+    it("Should reject proposals over the cap until one is finished", async () => {
+      const first = await createProposal(wallet, walletAuthority);
+      await createProposal(wallet, walletAuthority);
+
+      try {
+        await createProposal(wallet, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/TooManyOpenProposals/);
+      }
+
+      await cancelProposal(wallet, first, walletAuthority);
+      await createProposal(wallet, walletAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.activeProposalCount.toNumber()).to.equal(2);
+    });
+
+    it("Should free a slot when a proposal executes", async () => {
+      await setPolicy(wallet, walletAuthority, { maxOpenProposals: 3 });
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      try {
+        await createProposal(wallet, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/TooManyOpenProposals/);
+      }
+
+      await executeProposal(wallet, proposal, signer1);
+      await createProposal(wallet, walletAuthority);
+    });
+  });
+
+  describe("Signer Nomination", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+    let nominee: Keypair;
+
+    const nominateSigner = async (address: PublicKey, role: any) => {
+      await program.methods
+        .nominateSigner(address, role)
+        .accounts({ walletConfig: wallet, admin: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+    };
+
+    const acceptSignerRole = async (caller: Keypair) => {
+      await program.methods
+        .acceptSignerRole()
+        .accounts({ walletConfig: wallet, nominee: caller.publicKey })
+        .signers([caller])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      nominee = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 2);
+    });
+
+    it("Should only add a nominated signer once it accepts", async () => {
+      await nominateSigner(nominee.publicKey, { treasurer: {} });
+
+      let walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.signers.map((signer) => signer.toString())).to.not.include(
+        nominee.publicKey.toString()
+      );
+      expect(walletConfigAccount.pendingSigner.address.toString()).to.equal(nominee.publicKey.toString());
+
+      // The nominee can't vote before accepting
+      const proposal = await createProposal(wallet, walletAuthority);
+      try {
+        await approveProposal(wallet, proposal, nominee);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/PendingSignerNotAccepted/);
+      }
+
+      // Nobody else can accept on the nominee's behalf
+      try {
+        await acceptSignerRole(signer2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized/);
+      }
+
+      await acceptSignerRole(nominee);
+      await approveProposal(wallet, proposal, nominee);
+
+      walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.pendingSigner).to.be.null;
+      const member = walletConfigAccount.members.find(m => m.address.toString() === nominee.publicKey.toString());
+      expect(member.role).to.deep.equal({ treasurer: {} });
+    });
+
+    it("Should fail to accept without a pending nomination", async () => {
+      try {
+        await acceptSignerRole(signer2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NoSignerNominationPending/);
+      }
+    });
+
+    it("Should not nominate an existing signer", async () => {
+      try {
+        await nominateSigner(signer1.publicKey, { member: {} });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/MemberAlreadyExists/);
+      }
+    });
+  });
+
+  describe("Config Change Log", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const setSpendingLimits = async () => {
+      await program.methods
+        .setSpendingLimits(new BN(LAMPORTS_PER_SOL), new BN(86400))
+        .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should log spending limit changes", async () => {
+      await setSpendingLimits();
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.configChanges.length).to.equal(1);
+      const entry = walletConfigAccount.configChanges[0];
+      expect(entry.kind).to.deep.equal({ spendingLimits: {} });
+      expect(entry.actor.toString()).to.equal(walletAuthority.publicKey.toString());
+      expect(entry.timestamp.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("Should log signer changes", async () => {
+      await addMember(wallet, signer2.publicKey, { member: {} }, walletAuthority);
+      await removeMember(wallet, signer2.publicKey, walletAuthority);
+      await updateSigners(wallet, [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey], 1, walletAuthority);
+      await updateSigners(wallet, [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey], 1, signer1);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      const entries = walletConfigAccount.configChanges.slice(-3);
+      expect(entries.map((entry) => entry.kind)).to.deep.equal([
+        { memberAdded: {} },
+        { memberRemoved: {} },
+        { signers: {} },
+      ]);
+      expect(entries[0].actor.toString()).to.equal(walletAuthority.publicKey.toString());
+      expect(entries[1].actor.toString()).to.equal(walletAuthority.publicKey.toString());
+      // The signer change is credited to the approval that applied it
+      expect(entries[2].actor.toString()).to.equal(signer1.publicKey.toString());
+    });
+
+    it("Should drop the oldest entries once the log is full", async () => {
+      for (let i = 0; i < 8; i++) {
+        await setSpendingLimits();
+      }
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.configChanges.length).to.equal(8);
+      walletConfigAccount.configChanges.forEach((entry) => {
+        expect(entry.kind).to.deep.equal({ spendingLimits: {} });
+      });
+    });
+  });
+
+  describe("Minimum Voting Period", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await setPolicy(wallet, walletAuthority, { minVotingPeriod: new BN(3) });
+    });
+
+    it("Should fail to execute before the voting period has passed", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      try {
+        await executeProposal(wallet, proposal, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/VotingPeriodNotElapsed/);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should execute once the voting period has passed", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      await sleep(4);
+      await executeProposal(wallet, proposal, signer1);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should fail to set a negative voting period", async () => {
+      try {
+        await setPolicy(wallet, walletAuthority, { minVotingPeriod: new BN(-1) });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidTimeout/);
+      }
+    });
+  });
+
+  describe("Purging Expired Proposals", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const purgeExpired = async (closeTerminal: boolean, remainingAccounts: AccountMeta[]) => {
+      await program.methods
+        .purgeExpired(closeTerminal)
+        .accounts({ walletConfig: wallet })
+        .remainingAccounts(remainingAccounts)
+        .rpc();
+    };
+
+    const writable = (pubkey: PublicKey): AccountMeta => ({ pubkey, isSigner: false, isWritable: true });
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should expire only the proposals past their expiration", async () => {
+      const expiring = await createProposal(wallet, walletAuthority, { regular: {} }, [], 2);
+      const expiringApproved = await createProposal(wallet, walletAuthority, { regular: {} }, [], 2);
+      await approveProposal(wallet, expiringApproved, walletAuthority);
+      const open = await createProposal(wallet, walletAuthority);
+
+      await sleep(3);
+      await purgeExpired(false, [writable(expiring), writable(expiringApproved), writable(open)]);
+
+      expect((await program.account.proposal.fetch(expiring)).status).to.deep.equal({ expired: {} });
+      expect((await program.account.proposal.fetch(expiringApproved)).status).to.deep.equal({ expired: {} });
+      expect((await program.account.proposal.fetch(open)).status).to.deep.equal({ pending: {} });
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.activeProposalCount.toNumber()).to.equal(1);
+      expect(walletConfigAccount.terminalProposalCount.toNumber()).to.equal(2);
+    });
+
+    it("Should close finished proposals and refund their fee payer", async () => {
+      const expiring = await createProposal(wallet, signer1, { regular: {} }, [], 2);
+      const cancelled = await createProposal(wallet, signer1);
+      await cancelProposal(wallet, cancelled, signer1);
+      const open = await createProposal(wallet, signer1);
+
+      await sleep(3);
+      const balanceBefore = await provider.connection.getBalance(signer1.publicKey);
+      await purgeExpired(true, [writable(expiring), writable(cancelled), writable(open), writable(signer1.publicKey)]);
+
+      expect(await provider.connection.getAccountInfo(expiring)).to.be.null;
+      expect(await provider.connection.getAccountInfo(cancelled)).to.be.null;
+      expect((await program.account.proposal.fetch(open)).status).to.deep.equal({ pending: {} });
+      const balanceAfter = await provider.connection.getBalance(signer1.publicKey);
+      expect(balanceAfter).to.be.greaterThan(balanceBefore);
+    });
+
+    it("Should leave finished proposals open without their fee payer", async () => {
+      const cancelled = await createProposal(wallet, signer1);
+      await cancelProposal(wallet, cancelled, signer1);
+
+      await purgeExpired(true, [writable(cancelled)]);
+
+      expect((await program.account.proposal.fetch(cancelled)).status).to.deep.equal({ cancelled: {} });
+    });
+  });
+
+  describe("Required Approvers", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should keep a proposal pending until its required approver approves", async () => {
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [], 1800, "Legal sign-off", [], [
+        signer2.publicKey,
+      ]);
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(2);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+
+      await approveProposal(wallet, proposal, signer2);
+
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should fail to require approval from a non-signer", async () => {
+      try {
+        await createProposal(wallet, walletAuthority, { regular: {} }, [], 1800, "Test proposal", [], [nonSigner.publicKey]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidRequiredApprovers/);
+      }
+    });
+
+    it("Should fail to list a required approver twice", async () => {
+      try {
+        await createProposal(wallet, walletAuthority, { regular: {} }, [], 1800, "Test proposal", [], [
+          signer2.publicKey,
+          signer2.publicKey,
+        ]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvalidRequiredApprovers/);
+      }
+    });
+
+    it("Should make required approvers sign in order when the policy says so", async () => {
+      await setPolicy(wallet, walletAuthority, { orderedRequiredApprovers: true });
+      const proposal = await createProposal(wallet, walletAuthority, { regular: {} }, [], 1800, "Sign-off chain", [], [
+        signer1.publicKey,
+        signer2.publicKey,
+      ]);
+
+      try {
+        await approveProposal(wallet, proposal, signer2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/OutOfOrderApproval/);
+      }
+
+      // Approvers outside the chain are not ordered
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await approveProposal(wallet, proposal, signer2);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should not auto-approve a proposer waiting on earlier required approvers", async () => {
+      await setPolicy(wallet, walletAuthority, { orderedRequiredApprovers: true, autoApproveProposer: true });
+      const proposal = await createProposal(wallet, signer2, { regular: {} }, [], 1800, "Sign-off chain", [], [
+        signer1.publicKey,
+        signer2.publicKey,
+      ]);
+      await setPolicy(wallet, walletAuthority, { autoApproveProposer: false });
+
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(0);
+
+      await approveProposal(wallet, proposal, signer1);
+      await approveProposal(wallet, proposal, signer2);
+
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should skip out-of-order proposals in a batch approval", async () => {
+      await setPolicy(wallet, walletAuthority, { orderedRequiredApprovers: true });
+      const chained = await createProposal(wallet, walletAuthority, { regular: {} }, [], 1800, "Sign-off chain", [], [
+        signer1.publicKey,
+        signer2.publicKey,
+      ]);
+      const unchained = await createProposal(wallet, walletAuthority);
+
+      await program.methods
+        .approveProposalsBatch()
+        .accounts({
+          walletConfig: wallet,
+          approver: signer2.publicKey,
+        })
+        .remainingAccounts([chained, unchained].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+        .signers([signer2])
+        .rpc();
+
+      expect((await program.account.proposal.fetch(chained)).approvals.length).to.equal(0);
+      const unchainedAccount = await program.account.proposal.fetch(unchained);
+      expect(unchainedAccount.approvals.map((approval) => approval.signer.toBase58())).to.deep.equal([
+        signer2.publicKey.toBase58(),
+      ]);
+    });
+  });
+
+  describe("Removed Signer Approvals", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const writable = (pubkey: PublicKey): AccountMeta => ({ pubkey, isSigner: false, isWritable: true });
+
+    // An approved proposal backed by signer1 and signer2
+    const approvedProposal = async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await approveProposal(wallet, proposal, signer2);
+      return proposal;
+    };
+
+    beforeEach(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2
+      );
+    });
+
+    it("Should retain a removed signer's approvals by default", async () => {
+      const proposal = await approvedProposal();
+
+      await removeMember(wallet, signer2.publicKey, walletAuthority, [writable(proposal)]);
+
+      let proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(2);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+
+      // The removed signer's approval still counts toward execution
+      await executeProposal(wallet, proposal, signer1);
+      proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should invalidate a removed member's approvals when the policy says so", async () => {
+      await setPolicy(wallet, walletAuthority, { invalidateRemovedApprovals: true });
+      const proposal = await approvedProposal();
+      const pending = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, pending, signer2);
+      const unlisted = await approvedProposal();
+
+      await removeMember(wallet, signer2.publicKey, walletAuthority, [writable(proposal), writable(pending)]);
+
+      // A proposal left out of the removal stays approved but can't execute
+      try {
+        await executeProposal(wallet, unlisted, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/SignerSetChanged/);
+      }
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(1);
+      expect(proposalAccount.approvals[0].signer.toString()).to.equal(signer1.publicKey.toString());
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+      expect(proposalAccount.approvedAt).to.be.null;
+      expect((await program.account.proposal.fetch(pending)).approvals.length).to.equal(0);
+    });
+
+    it("Should keep proposals approved that still meet the threshold", async () => {
+      await setPolicy(wallet, walletAuthority, { invalidateRemovedApprovals: true });
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+      await approveProposal(wallet, proposal, signer1);
+      await approveProposal(wallet, proposal, signer2);
+
+      await removeMember(wallet, signer2.publicKey, walletAuthority, [writable(proposal)]);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(2);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should invalidate approvals of signers dropped by update_signers", async () => {
+      await setPolicy(wallet, walletAuthority, { invalidateRemovedApprovals: true });
+      const proposal = await approvedProposal();
+      const newSigners = [walletAuthority.publicKey, signer1.publicKey, signer3.publicKey];
+
+      await updateSigners(wallet, newSigners, 2, walletAuthority);
+      await updateSigners(wallet, newSigners, 2, signer1);
+      await updateSigners(wallet, newSigners, 2, signer2, [writable(proposal)]);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.approvals.length).to.equal(1);
+      expect(proposalAccount.status).to.deep.equal({ pending: {} });
+    });
+  });
+
+  describe("Execution Bounty", () => {
+    const bounty = 5_000_000;
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+      await setPolicy(wallet, walletAuthority, { executionBounty: new BN(bounty) });
+
+      // Fund the wallet PDA so it can pay the bounty
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({ fromPubkey: walletAuthority.publicKey, toPubkey: wallet, lamports: LAMPORTS_PER_SOL })
+        ),
+        [walletAuthority]
+      );
+    });
+
+    it("Should pay the executor the bounty from the wallet", async () => {
+      const proposal = await createProposal(wallet, walletAuthority);
+      await approveProposal(wallet, proposal, walletAuthority);
+
+      const executorBefore = await provider.connection.getBalance(signer1.publicKey);
+      const walletBefore = await provider.connection.getBalance(wallet);
+      await executeProposal(wallet, proposal, signer1);
+
+      expect(await provider.connection.getBalance(signer1.publicKey)).to.equal(executorBefore + bounty);
+      expect(await provider.connection.getBalance(wallet)).to.equal(walletBefore - bounty);
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.spendingUsed.toNumber()).to.equal(bounty);
+    });
+
+    it("Should fail to set a bounty above the maximum", async () => {
+      try {
+        await setPolicy(wallet, walletAuthority, { executionBounty: new BN(LAMPORTS_PER_SOL) });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/ExecutionBountyTooLarge/);
+      }
+    });
+  });
+
+  describe("Governed Spending Limit Increases", () => {
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const setSpendingLimits = async (limit: number, period: number) => {
+      await program.methods
+        .setSpendingLimits(new BN(limit), new BN(period))
+        .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+    };
+
+    before(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        1,
+        { spendingLimit: new BN(LAMPORTS_PER_SOL), adminThreshold: 2 }
+      );
+      await setPolicy(wallet, walletAuthority, { governLimitIncreases: true });
+    });
+
+    it("Should block the authority from raising the spending limit", async () => {
+      try {
+        await setSpendingLimits(2 * LAMPORTS_PER_SOL, 86400);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/SpendingLimitIncreaseRequiresProposal/);
+      }
+    });
+
+    it("Should block the authority from shortening the spending period", async () => {
+      try {
+        await setSpendingLimits(LAMPORTS_PER_SOL, 3600);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/SpendingLimitIncreaseRequiresProposal/);
+      }
+    });
+
+    it("Should let the authority lower the spending limit", async () => {
+      await setSpendingLimits(LAMPORTS_PER_SOL / 2, 86400);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.spendingLimit.toNumber()).to.equal(LAMPORTS_PER_SOL / 2);
+    });
+
+    it("Should raise the spending limit through an admin-threshold proposal", async () => {
+      const category = { spendingLimitChange: { limit: new BN(3 * LAMPORTS_PER_SOL), period: new BN(86400) } };
+      const proposal = await createProposal(wallet, signer1, category);
+      await approveProposal(wallet, proposal, walletAuthority);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ pending: {} });
+
+      await approveProposal(wallet, proposal, signer1);
+      await executeProposal(wallet, proposal, signer1);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.spendingLimit.toNumber()).to.equal(3 * LAMPORTS_PER_SOL);
+    });
+  });
+
+  describe("Invariant Checks", () => {
+    // Flags returned by check_invariants
+    const INVARIANT_THRESHOLD = 1 << 0;
+    const INVARIANT_SPENDING = 1 << 2;
+
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    const checkInvariants = (failOnCritical: boolean = false): Promise<number> =>
+      program.methods.checkInvariants(failOnCritical).accounts({ walletConfig: wallet }).view();
+
+    beforeEach(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey, signer2.publicKey],
+        2,
+        { adminThreshold: 2 }
+      );
+    });
+
+    it("Should report no violations for a healthy wallet", async () => {
+      expect(await checkInvariants(true)).to.equal(0);
+    });
+
+    it("Should flag a spending period that is not positive", async () => {
+      await program.methods
+        .setSpendingLimits(new BN(LAMPORTS_PER_SOL), new BN(-1))
+        .accounts({ walletConfig: wallet, approver: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+
+      expect(await checkInvariants()).to.equal(INVARIANT_SPENDING);
+      // A broken spending window is not critical
+      expect(await checkInvariants(true)).to.equal(INVARIANT_SPENDING);
+    });
+
+    it("Should flag thresholds the active voting signers can't meet", async () => {
+      // The authority's votes stop counting, then one of the two remaining
+      // voters is deactivated
+      await setPolicy(wallet, walletAuthority, { excludeAuthorityVotes: true });
+      await program.methods
+        .setMemberActive(signer2.publicKey, false)
+        .accounts({ walletConfig: wallet, admin: walletAuthority.publicKey })
+        .signers([walletAuthority])
+        .rpc();
+
+      expect(await checkInvariants()).to.equal(INVARIANT_THRESHOLD);
+      try {
+        await checkInvariants(true);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/InvariantViolated/);
+      }
+    });
+  });
+
+  describe("Account Versions", () => {
+    const WALLET_CONFIG_VERSION = 2;
+    const PROPOSAL_VERSION = 1;
+
+    it("Should stamp new accounts with the current version and accept them", async () => {
+      const walletAuthority = await fundedKeypair();
+      const wallet = await initializeWallet(
+        walletAuthority,
+        [walletAuthority.publicKey, signer1.publicKey],
+        1
+      );
+      const proposal = await createProposal(wallet, walletAuthority);
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(wallet);
+      expect(walletConfigAccount.version).to.equal(WALLET_CONFIG_VERSION);
+      expect((await program.account.proposal.fetch(proposal)).version).to.equal(PROPOSAL_VERSION);
+
+      await approveProposal(wallet, proposal, signer1);
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Should reject a wallet config written by a newer program version", async () => {
+      const walletConfigAccount = await program.account.walletConfig.fetch(FUTURE_VERSION_WALLET);
+      expect(walletConfigAccount.version).to.equal(255);
+
+      try {
+        await program.methods
+          .checkInvariants(false)
+          .accounts({ walletConfig: FUTURE_VERSION_WALLET })
+          .view();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/UnsupportedAccountVersion/);
+      }
+    });
+
+    it("Should refuse a wallet config written before accounts were versioned", async () => {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(UNVERSIONED_WALLET_AUTHORITY.publicKey, LAMPORTS_PER_SOL)
+      );
+      const before = await provider.connection.getAccountInfo(UNVERSIONED_WALLET);
+
+      try {
+        await program.methods
+          .migrateWalletConfig()
+          .accounts({ walletConfig: UNVERSIONED_WALLET, authority: UNVERSIONED_WALLET_AUTHORITY.publicKey })
+          .signers([UNVERSIONED_WALLET_AUTHORITY])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/UnsupportedAccountVersion/);
+      }
+
+      const after = await provider.connection.getAccountInfo(UNVERSIONED_WALLET);
+      expect(after.data.equals(before.data)).to.be.true;
+    });
+  });
+
+  describe("Wallet Config Migration", () => {
+    const migrateWalletConfig = (wallet: PublicKey, authority: Keypair) =>
+      program.methods
+        .migrateWalletConfig()
+        .accounts({ walletConfig: wallet, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+    let walletAuthority: Keypair;
+    let wallet: PublicKey;
+
+    beforeEach(async () => {
+      walletAuthority = await fundedKeypair();
+      wallet = await initializeWallet(walletAuthority, [walletAuthority.publicKey, signer1.publicKey], 1);
+    });
+
+    it("Should leave an up-to-date wallet config unchanged", async () => {
+      const before = await provider.connection.getAccountInfo(wallet);
+
+      await migrateWalletConfig(wallet, walletAuthority);
+      // Migrating is idempotent
+      await migrateWalletConfig(wallet, walletAuthority);
+
+      const after = await provider.connection.getAccountInfo(wallet);
+      expect(after.data.equals(before.data)).to.be.true;
+      expect(after.lamports).to.equal(before.lamports);
+    });
+
+    it("Should only let the authority migrate a wallet config", async () => {
+      try {
+        await migrateWalletConfig(wallet, signer1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/NotAuthorized/);
+      }
+    });
+
+    it("Should migrate a version 1 wallet config to the current layout", async () => {
+      try {
+        await program.methods
+          .checkInvariants(false)
+          .accounts({ walletConfig: LEGACY_V1_WALLET })
+          .view();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/UnsupportedAccountVersion/);
+      }
+
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(LEGACY_V1_WALLET_AUTHORITY.publicKey, LAMPORTS_PER_SOL)
+      );
+      const before = await provider.connection.getAccountInfo(LEGACY_V1_WALLET);
+
+      await migrateWalletConfig(LEGACY_V1_WALLET, LEGACY_V1_WALLET_AUTHORITY);
+
+      // Version 2 appended eight awaited dependency slots
+      const after = await provider.connection.getAccountInfo(LEGACY_V1_WALLET);
+      expect(after.data.length).to.equal(before.data.length + 64);
+      expect(after.lamports).to.equal(
+        await provider.connection.getMinimumBalanceForRentExemption(after.data.length)
+      );
+
+      const walletConfigAccount = await program.account.walletConfig.fetch(LEGACY_V1_WALLET);
+      expect(walletConfigAccount.version).to.equal(2);
+      expect(walletConfigAccount.signers.map((s) => s.toBase58())).to.deep.equal([
+        LEGACY_V1_WALLET_AUTHORITY.publicKey.toBase58(),
+      ]);
+      // The slots start free rather than reading the stale bytes
+      expect(walletConfigAccount.awaitedDependencies.every((id) => id.isZero())).to.be.true;
+
+      await program.methods
+        .checkInvariants(false)
+        .accounts({ walletConfig: LEGACY_V1_WALLET })
+        .view();
+    });
+
+    it("Should refuse to migrate a wallet config from a newer program version", async () => {
+      try {
+        await migrateWalletConfig(FUTURE_VERSION_WALLET, walletAuthority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.match(/UnsupportedAccountVersion/);
+      }
+    });
+  });
 });